@@ -2,8 +2,25 @@ use bevy::prelude::*;
 use bevy::input::mouse::{MouseWheel, MouseMotion};
 use bevy::input::touch::{TouchInput, TouchPhase};
 use crate::config::*;
+use crate::world::VoxelWorld;
 use std::collections::HashMap;
 
+/// Plugin for the orbital camera and its mouse, keyboard and touch controls
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TouchState::default())
+            .add_systems(Startup, setup_camera)
+            .add_systems(Update, (
+                camera_rotation_system,
+                camera_zoom_system,
+                camera_pan_system,
+                camera_touch_system,
+            ));
+    }
+}
+
 /// Component to mark the orbital camera
 #[derive(Component)]
 pub struct OrbitalCamera {
@@ -37,8 +54,16 @@ impl Default for OrbitalCamera {
 }
 
 /// Setup the camera
-pub fn setup_camera(mut commands: Commands) {
-    let camera = OrbitalCamera::default();
+pub fn setup_camera(mut commands: Commands, world: Res<VoxelWorld>) {
+    // Aim at the centre of the world, whatever its configured size
+    let camera = OrbitalCamera {
+        target: Vec3::new(
+            (world.width() as f32 * VOXEL_SIZE) / 2.0,
+            (world.height() as f32 * VOXEL_SIZE) / 4.0,
+            (world.depth() as f32 * VOXEL_SIZE) / 2.0,
+        ),
+        ..default()
+    };
     let position = calculate_camera_position(&camera);
 
     commands.spawn((
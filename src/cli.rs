@@ -0,0 +1,102 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use plant_evolution_sim::config::SimConfig;
+use plant_evolution_sim::snapshot::Snapshot;
+
+/// 3D plant growth and evolution simulator
+#[derive(Parser, Debug)]
+#[command(name = "plant-evolution-sim", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub sim: SimArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the simulation in a window (the default)
+    Run,
+    /// Run the simulation without a window or renderer, then exit
+    Headless {
+        /// Simulated seconds to run for
+        #[arg(long, default_value_t = 60.0)]
+        duration: f32,
+
+        /// Stream statistics to this file as the run goes (JSON lines for .jsonl/.json, CSV otherwise)
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Save a snapshot of the final state to this file
+        #[arg(long)]
+        save: Option<PathBuf>,
+    },
+}
+
+/// Options shared by every subcommand
+#[derive(Args, Debug)]
+pub struct SimArgs {
+    /// TOML file overriding the default simulation parameters
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Seed for a reproducible run
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    /// World dimensions in voxels, as WIDTHxHEIGHTxDEPTH
+    #[arg(long, global = true, value_parser = parse_world_size)]
+    pub world_size: Option<(usize, usize, usize)>,
+
+    /// Number of plants to spawn at startup
+    #[arg(long, global = true)]
+    pub initial_seeds: Option<usize>,
+
+    /// Snapshot file to continue a saved run from
+    #[arg(long, global = true)]
+    pub load: Option<PathBuf>,
+}
+
+impl SimArgs {
+    /// Build the simulation config from the config file and command-line overrides
+    pub fn to_config(&self) -> Result<SimConfig, String> {
+        let mut config = match &self.config {
+            Some(path) => SimConfig::load(path)?,
+            None => SimConfig::default(),
+        };
+
+        if let Some(seed) = self.seed {
+            config.seed = Some(seed);
+        }
+        if let Some((width, height, depth)) = self.world_size {
+            config.world_width = width;
+            config.world_height = height;
+            config.world_depth = depth;
+        }
+        if let Some(count) = self.initial_seeds {
+            config.initial_seed_count = count;
+        }
+
+        Ok(config)
+    }
+
+    /// Read the snapshot given with `--load`, if any
+    pub fn load_snapshot(&self) -> Result<Option<Snapshot>, String> {
+        self.load.as_deref().map(Snapshot::load).transpose()
+    }
+}
+
+/// Parse a world size such as `128x64x128`
+fn parse_world_size(value: &str) -> Result<(usize, usize, usize), String> {
+    let dims: Vec<usize> = value
+        .split('x')
+        .map(|part| part.trim().parse::<usize>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid world size '{}', expected WIDTHxHEIGHTxDEPTH", value))?;
+
+    match dims[..] {
+        [width, height, depth] if width > 0 && height > 1 && depth > 0 => Ok((width, height, depth)),
+        _ => Err(format!("invalid world size '{}', expected WIDTHxHEIGHTxDEPTH", value)),
+    }
+}
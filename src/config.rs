@@ -1,5 +1,9 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
 /// Configuration constants for the plant evolution simulation
-/// These values can be changed and require recompilation
+/// These are the defaults for `SimConfig`, which can override them at runtime from a TOML file
 
 /// World dimensions (in voxels)
 pub const WORLD_WIDTH: usize = 64;
@@ -12,16 +16,25 @@ pub const VOXEL_SIZE: f32 = 0.5;
 /// Initial number of seed plants
 pub const INITIAL_SEED_COUNT: usize = 10;
 
+/// Random seed for reproducible runs (None = pick a random seed at startup)
+pub const RNG_SEED: Option<u64> = None;
+
 /// Simulation parameters
-pub const TICKS_PER_SECOND: f32 = 10.0;
+pub const TICKS_PER_SECOND: f32 = 10.0; // Fixed simulation rate, independent of frame rate
 pub const SUNLIGHT_MAX: f32 = 100.0; // Maximum sunlight value at surface
 pub const SUNLIGHT_FALLOFF: f32 = 0.9; // Light reduction per voxel layer downward
+pub const DIFFUSE_LIGHT_FRACTION: f32 = 0.3; // Share of sunlight arriving as diffuse skylight from straight above
+
+/// Sun path
+pub const SUN_SUMMER_ELEVATION: f32 = 70.0; // Highest noon sun in mid-summer (degrees)
+pub const SUN_WINTER_ELEVATION: f32 = 25.0; // Lowest noon sun in mid-winter (degrees)
+pub const MAX_SUN_RAY_SLOPE: f32 = 4.0; // Caps shadow length near sunrise and sunset (voxels per voxel of height)
+pub const SUN_ANGLE_STEP: f32 = 5.0; // Degrees the sun moves before the whole world is relit
 
 /// Soil parameters
 pub const SOIL_NUTRIENT_MAX: f32 = 100.0;
 pub const SOIL_WATER_MAX: f32 = 100.0;
 pub const NUTRIENT_REGEN_RATE: f32 = 0.1; // Per tick
-pub const WATER_REGEN_RATE: f32 = 0.2; // Per tick
 
 /// Plant growth parameters
 pub const BASE_GROWTH_COST: f32 = 10.0; // Energy cost per new voxel
@@ -29,6 +42,30 @@ pub const BASE_MAINTENANCE_COST: f32 = 0.3; // Energy per voxel per tick (increa
 pub const PHOTOSYNTHESIS_EFFICIENCY: f32 = 0.5; // Energy per light per tick
 pub const ROOT_ABSORPTION_RATE: f32 = 1.0; // Resource absorption per tick
 
+/// Maintenance cost multipliers for each plant organ
+pub const STEM_MAINTENANCE: f32 = 0.5; // Woody tissue is cheap to keep alive
+pub const LEAF_MAINTENANCE: f32 = 1.5; // Leaves respire the most
+pub const ROOT_MAINTENANCE: f32 = 1.0;
+pub const FLOWER_MAINTENANCE: f32 = 2.0;
+pub const SEED_MAINTENANCE: f32 = 0.1; // Dormant seeds barely respire
+pub const STORAGE_MAINTENANCE: f32 = 0.3;
+
+/// Internal transport and energy storage
+/// Energy from leaves and roots flows through the plant's voxels to its base
+pub const TRANSPORT_LOSS: f32 = 0.02; // Fraction of flowing energy lost per voxel travelled
+pub const TRANSPORT_CAPACITY: f32 = 100.0; // Energy per second a single voxel can carry
+pub const STORAGE_CAPACITY: f32 = 100.0; // Reserves held per storage voxel
+pub const STORAGE_RATE: f32 = 5.0; // Energy per second moved in or out of each storage voxel
+pub const STORAGE_FILL_ENERGY: f32 = 100.0; // Energy above this is stored in reserves
+pub const STORAGE_DRAW_ENERGY: f32 = 30.0; // Reserves are drawn when energy falls below this
+
+/// Structural support
+/// Every plant voxel weighs 1; a stem breaks when the weight it supports, times its horizontal
+/// distance from the stem, exceeds its strength, or when the weight alone crushes it
+pub const STEM_STRENGTH: f32 = 100.0; // Bending moment a stem can hold, before the wood density multiplier
+pub const STEM_COMPRESSION_STRENGTH: f32 = 500.0; // Weight a stem can bear, before the wood density multiplier
+pub const WOOD_COST: f32 = 1.0; // Extra stem maintenance for the densest wood
+
 /// Reproduction parameters
 pub const MIN_REPRODUCTION_ENERGY: f32 = 100.0;
 pub const REPRODUCTION_ENERGY_COST: f32 = 50.0;
@@ -42,6 +79,10 @@ pub const MUTATION_STRENGTH: f32 = 0.1; // Max percentage change from mutation
 pub const STATS_UPDATE_INTERVAL: f32 = 1.0; // Seconds between stat updates
 pub const STATS_HISTORY_SIZE: usize = 1000; // Number of data points to keep
 
+/// Time control
+pub const MIN_TIME_SCALE: f32 = 0.25; // Slowest simulation speed multiplier
+pub const MAX_TIME_SCALE: f32 = 64.0; // Fastest simulation speed multiplier
+
 /// UI parameters
 pub const CAMERA_MOVE_SPEED: f32 = 50.0;
 pub const CAMERA_ROTATE_SPEED: f32 = 2.0;
@@ -49,4 +90,256 @@ pub const CAMERA_ZOOM_SPEED: f32 = 10.0;
 pub const CAMERA_INITIAL_DISTANCE: f32 = 100.0;
 
 /// Rendering parameters
-pub const CHUNK_SIZE: usize = 16; // Voxels per chunk dimension
+pub const CHUNK_SIZE: usize = 16; // Voxels per chunk dimension, for storage and re-meshing
+pub const MESHING_MODE: MeshingMode = MeshingMode::Greedy;
+
+/// How chunk meshes are built from voxel faces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MeshingMode {
+    Naive,  // One quad per visible voxel face
+    Greedy, // Coplanar faces of the same colour merged into larger quads
+}
+
+/// Terrain generation
+pub const TERRAIN_BASE_HEIGHT: f32 = 0.5; // Average surface height as a fraction of world height
+pub const TERRAIN_AMPLITUDE: f32 = 8.0; // Maximum deviation from the base height (voxels)
+pub const TERRAIN_FREQUENCY: f64 = 0.03; // Noise frequency (lower = broader hills)
+pub const TERRAIN_OCTAVES: usize = 4; // Noise layers (more = rougher detail)
+
+/// Soil fertility and moisture maps
+/// Each soil column gets multipliers for its nutrient/water capacity, nutrient regeneration and rainfall
+pub const FERTILITY_VARIATION: f32 = 0.6; // Fertility ranges over 1 ± this
+pub const FERTILITY_FREQUENCY: f64 = 0.05; // Noise frequency (lower = larger patches)
+pub const MOISTURE_VARIATION: f32 = 0.6; // Moisture ranges over 1 ± this
+pub const MOISTURE_FREQUENCY: f64 = 0.04;
+pub const MIN_SOIL_QUALITY: f32 = 0.05; // Lowest multiplier, so no soil is completely barren
+
+/// Hydrology parameters
+pub const RAIN_CHANCE: f32 = 0.02; // Chance per second that a rain event starts
+pub const RAIN_DURATION: f32 = 20.0; // Average length of a rain event (seconds)
+pub const RAIN_RATE: f32 = 3.0; // Water added to the top ground voxel per tick while raining
+pub const PERCOLATION_RATE: f32 = 0.1; // Fraction of water sinking one voxel per tick
+pub const LATERAL_DIFFUSION_RATE: f32 = 0.05; // Fraction of the water difference flowing sideways per tick (max 0.25)
+pub const EVAPORATION_RATE: f32 = 0.5; // Water lost per tick at full sunlight
+pub const WATER_TABLE_DEPTH: i32 = 12; // Voxels below the surface where the ground stays saturated
+
+/// Decomposition parameters
+pub const LITTER_NUTRIENTS_PER_MASS: f32 = 20.0; // Nutrients returned per voxel of dead plant mass
+pub const LITTER_DECAY_RATE: f32 = 0.01; // Fraction of a litter voxel's nutrients released per tick
+pub const LITTER_MIN_NUTRIENTS: f32 = 0.5; // Litter holding less than this has fully decomposed
+
+/// Temperature parameters (°C)
+pub const MEAN_TEMPERATURE: f32 = 12.0; // Yearly average at the base terrain height
+pub const SEASONAL_TEMPERATURE_RANGE: f32 = 14.0; // Swing above and below the mean over a year
+pub const DAILY_TEMPERATURE_RANGE: f32 = 4.0; // Swing above and below over a day
+pub const TEMPERATURE_LAPSE_RATE: f32 = 0.4; // Cooling per voxel of altitude
+pub const PHOTOSYNTHESIS_MIN_TEMPERATURE: f32 = 2.0; // No photosynthesis at or below this (before cold tolerance)
+pub const PHOTOSYNTHESIS_OPTIMAL_TEMPERATURE: f32 = 20.0; // Full photosynthesis rate from here
+pub const MAINTENANCE_Q10: f32 = 2.0; // Maintenance cost multiplier per 10°C above the optimum
+pub const FROST_TEMPERATURE: f32 = 0.0; // Exposed leaves take frost damage below this (before cold tolerance)
+pub const FROST_DAMAGE_RATE: f32 = 0.02; // Chance per second per degree of frost that an exposed leaf dies
+pub const COLD_TOLERANCE_COST: f32 = 0.3; // Extra maintenance for a fully cold-tolerant plant
+
+/// Snapshot file used by the quick-save (F5) and quick-load (F9) hotkeys
+pub const QUICKSAVE_PATH: &str = "quicksave.snapshot";
+
+/// Day/night and seasonal cycle lengths
+pub const DAY_LENGTH: f32 = 60.0; // Seconds per day
+pub const YEAR_LENGTH: f32 = 300.0; // Seconds per year
+
+/// Runtime simulation parameters, loaded from a TOML file
+/// Any field missing from the file keeps its default from the constants above
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimConfig {
+    // World
+    pub world_width: usize,
+    pub world_height: usize,
+    pub world_depth: usize,
+    pub initial_seed_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    // Terrain
+    pub terrain_base_height: f32,
+    pub terrain_amplitude: f32,
+    pub terrain_frequency: f64,
+    pub terrain_octaves: usize,
+
+    // Simulation
+    pub ticks_per_second: f32,
+    pub sunlight_max: f32,
+    pub sunlight_falloff: f32,
+    pub diffuse_light_fraction: f32,
+    pub day_length: f32,
+    pub year_length: f32,
+
+    // Soil
+    pub soil_nutrient_max: f32,
+    pub soil_water_max: f32,
+    pub nutrient_regen_rate: f32,
+    pub fertility_variation: f32,
+    pub fertility_frequency: f64,
+    pub moisture_variation: f32,
+    pub moisture_frequency: f64,
+
+    // Hydrology
+    pub rain_chance: f32,
+    pub rain_duration: f32,
+    pub rain_rate: f32,
+    pub percolation_rate: f32,
+    pub lateral_diffusion_rate: f32,
+    pub evaporation_rate: f32,
+    pub water_table_depth: i32,
+
+    // Decomposition
+    pub litter_nutrients_per_mass: f32,
+    pub litter_decay_rate: f32,
+    pub litter_min_nutrients: f32,
+
+    // Temperature
+    pub mean_temperature: f32,
+    pub seasonal_temperature_range: f32,
+    pub daily_temperature_range: f32,
+    pub temperature_lapse_rate: f32,
+    pub photosynthesis_min_temperature: f32,
+    pub photosynthesis_optimal_temperature: f32,
+    pub maintenance_q10: f32,
+    pub frost_temperature: f32,
+    pub frost_damage_rate: f32,
+    pub cold_tolerance_cost: f32,
+
+    // Plant growth
+    pub base_growth_cost: f32,
+    pub base_maintenance_cost: f32,
+    pub photosynthesis_efficiency: f32,
+    pub root_absorption_rate: f32,
+    pub stem_maintenance: f32,
+    pub leaf_maintenance: f32,
+    pub root_maintenance: f32,
+    pub flower_maintenance: f32,
+    pub seed_maintenance: f32,
+    pub storage_maintenance: f32,
+
+    // Transport and storage
+    pub transport_loss: f32,
+    pub transport_capacity: f32,
+    pub storage_capacity: f32,
+    pub storage_rate: f32,
+    pub storage_fill_energy: f32,
+    pub storage_draw_energy: f32,
+
+    // Structural support
+    pub stem_strength: f32,
+    pub stem_compression_strength: f32,
+    pub wood_cost: f32,
+
+    // Reproduction
+    pub min_reproduction_energy: f32,
+    pub reproduction_energy_cost: f32,
+    pub seed_dispersal_range: i32,
+
+    // Evolution
+    pub mutation_rate: f32,
+    pub mutation_strength: f32,
+
+    // Statistics
+    pub stats_update_interval: f32,
+    pub stats_history_size: usize,
+
+    // Rendering
+    pub meshing_mode: MeshingMode,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            world_width: WORLD_WIDTH,
+            world_height: WORLD_HEIGHT,
+            world_depth: WORLD_DEPTH,
+            initial_seed_count: INITIAL_SEED_COUNT,
+            seed: RNG_SEED,
+            terrain_base_height: TERRAIN_BASE_HEIGHT,
+            terrain_amplitude: TERRAIN_AMPLITUDE,
+            terrain_frequency: TERRAIN_FREQUENCY,
+            terrain_octaves: TERRAIN_OCTAVES,
+            ticks_per_second: TICKS_PER_SECOND,
+            sunlight_max: SUNLIGHT_MAX,
+            sunlight_falloff: SUNLIGHT_FALLOFF,
+            diffuse_light_fraction: DIFFUSE_LIGHT_FRACTION,
+            day_length: DAY_LENGTH,
+            year_length: YEAR_LENGTH,
+            soil_nutrient_max: SOIL_NUTRIENT_MAX,
+            soil_water_max: SOIL_WATER_MAX,
+            nutrient_regen_rate: NUTRIENT_REGEN_RATE,
+            fertility_variation: FERTILITY_VARIATION,
+            fertility_frequency: FERTILITY_FREQUENCY,
+            moisture_variation: MOISTURE_VARIATION,
+            moisture_frequency: MOISTURE_FREQUENCY,
+            rain_chance: RAIN_CHANCE,
+            rain_duration: RAIN_DURATION,
+            rain_rate: RAIN_RATE,
+            percolation_rate: PERCOLATION_RATE,
+            lateral_diffusion_rate: LATERAL_DIFFUSION_RATE,
+            evaporation_rate: EVAPORATION_RATE,
+            water_table_depth: WATER_TABLE_DEPTH,
+            litter_nutrients_per_mass: LITTER_NUTRIENTS_PER_MASS,
+            litter_decay_rate: LITTER_DECAY_RATE,
+            litter_min_nutrients: LITTER_MIN_NUTRIENTS,
+            mean_temperature: MEAN_TEMPERATURE,
+            seasonal_temperature_range: SEASONAL_TEMPERATURE_RANGE,
+            daily_temperature_range: DAILY_TEMPERATURE_RANGE,
+            temperature_lapse_rate: TEMPERATURE_LAPSE_RATE,
+            photosynthesis_min_temperature: PHOTOSYNTHESIS_MIN_TEMPERATURE,
+            photosynthesis_optimal_temperature: PHOTOSYNTHESIS_OPTIMAL_TEMPERATURE,
+            maintenance_q10: MAINTENANCE_Q10,
+            frost_temperature: FROST_TEMPERATURE,
+            frost_damage_rate: FROST_DAMAGE_RATE,
+            cold_tolerance_cost: COLD_TOLERANCE_COST,
+            base_growth_cost: BASE_GROWTH_COST,
+            base_maintenance_cost: BASE_MAINTENANCE_COST,
+            photosynthesis_efficiency: PHOTOSYNTHESIS_EFFICIENCY,
+            root_absorption_rate: ROOT_ABSORPTION_RATE,
+            stem_maintenance: STEM_MAINTENANCE,
+            leaf_maintenance: LEAF_MAINTENANCE,
+            root_maintenance: ROOT_MAINTENANCE,
+            flower_maintenance: FLOWER_MAINTENANCE,
+            seed_maintenance: SEED_MAINTENANCE,
+            storage_maintenance: STORAGE_MAINTENANCE,
+            transport_loss: TRANSPORT_LOSS,
+            transport_capacity: TRANSPORT_CAPACITY,
+            storage_capacity: STORAGE_CAPACITY,
+            storage_rate: STORAGE_RATE,
+            storage_fill_energy: STORAGE_FILL_ENERGY,
+            storage_draw_energy: STORAGE_DRAW_ENERGY,
+            stem_strength: STEM_STRENGTH,
+            stem_compression_strength: STEM_COMPRESSION_STRENGTH,
+            wood_cost: WOOD_COST,
+            min_reproduction_energy: MIN_REPRODUCTION_ENERGY,
+            reproduction_energy_cost: REPRODUCTION_ENERGY_COST,
+            seed_dispersal_range: SEED_DISPERSAL_RANGE,
+            mutation_rate: MUTATION_RATE,
+            mutation_strength: MUTATION_STRENGTH,
+            stats_update_interval: STATS_UPDATE_INTERVAL,
+            stats_history_size: STATS_HISTORY_SIZE,
+            meshing_mode: MESHING_MODE,
+        }
+    }
+}
+
+impl SimConfig {
+    /// Load a config from a TOML file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config {}: {}", path.display(), e))?;
+        Self::from_toml(&contents)
+            .map_err(|e| format!("Failed to parse config {}: {}", path.display(), e))
+    }
+
+    /// Parse a config from TOML text
+    pub fn from_toml(contents: &str) -> Result<Self, String> {
+        toml::from_str(contents).map_err(|e| e.to_string())
+    }
+}
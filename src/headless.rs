@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::path::PathBuf;
+use crate::statistics::{StatisticsHistory, StatsExporter};
+use crate::rng::SimulationRng;
+use crate::config::SimConfig;
+use crate::simulation::{SimulationPlugin, tick_duration};
+use crate::snapshot::{PendingSnapshot, Snapshot};
+
+/// Resource describing how long a headless run should last
+#[derive(Resource)]
+pub struct HeadlessRun {
+    pub duration: f32, // Simulated seconds
+}
+
+/// Optional inputs and outputs of a headless run
+#[derive(Default)]
+pub struct HeadlessOptions {
+    pub stats_out: Option<PathBuf>, // Statistics file (CSV, or JSON lines for .jsonl/.json)
+    pub load: Option<Snapshot>,     // Snapshot to continue from
+    pub save: Option<PathBuf>,      // Snapshot file written at the end
+}
+
+/// Build an app that runs the simulation without a window or renderer
+/// A config without a seed picks a random one, which is printed at startup
+pub fn build_headless_app(duration: f32, config: SimConfig) -> App {
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins)
+        // Advance simulated time by exactly one tick per update so runs are
+        // as fast as the CPU allows and independent of wall-clock time
+        .insert_resource(TimeUpdateStrategy::ManualDuration(tick_duration(&config)))
+        .insert_resource(HeadlessRun { duration })
+        .insert_resource(config)
+        .add_plugins(SimulationPlugin)
+        .add_systems(Last, headless_exit_system);
+
+    app
+}
+
+/// Run a headless app until it requests to exit
+pub fn run_to_completion(app: &mut App) -> AppExit {
+    app.finish();
+    app.cleanup();
+
+    loop {
+        app.update();
+
+        if let Some(exit) = app.should_exit() {
+            return exit;
+        }
+    }
+}
+
+/// Run the simulation headless for the given number of simulated seconds
+pub fn run_headless(duration: f32, config: SimConfig, options: HeadlessOptions) -> AppExit {
+    println!("Running headless simulation for {:.1} simulated seconds", duration);
+
+    let mut app = build_headless_app(duration, config.clone());
+
+    // Stream statistics as they are collected, tagged with the seed actually used
+    if let Some(path) = &options.stats_out {
+        let seed = app.world().resource::<SimulationRng>().seed();
+        match StatsExporter::create(path, seed, &config) {
+            Ok(exporter) => {
+                app.insert_resource(exporter);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                return AppExit::error();
+            }
+        }
+    }
+
+    if let Some(snapshot) = options.load {
+        app.insert_resource(PendingSnapshot(snapshot));
+    }
+    let exit = run_to_completion(&mut app);
+
+    if let Some(path) = &options.save {
+        if let Err(e) = Snapshot::capture(app.world_mut()).save(path) {
+            eprintln!("{}", e);
+            return AppExit::error();
+        }
+        println!("Saved snapshot to {}", path.display());
+    }
+
+    let stats = app.world().resource::<StatisticsHistory>();
+    if let Some(latest) = stats.snapshots.last() {
+        println!(
+            "Headless run finished at {:.1}s: population {}, species {}, biomass {} voxels",
+            latest.timestamp, latest.population, latest.species_count, latest.total_biomass
+        );
+    } else {
+        println!("Headless run finished with no surviving plants");
+    }
+
+    exit
+}
+
+/// System to stop the app once the requested simulated duration has elapsed
+/// `Time` counts from the start of this run, so a run loaded from a snapshot continues for `duration` more seconds
+fn headless_exit_system(
+    run: Res<HeadlessRun>,
+    time: Res<Time>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    if time.elapsed_secs() >= run.duration {
+        exit.write(AppExit::Success);
+    }
+}
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use crate::camera::OrbitalCamera;
+use crate::plant::{PlantBiology, PlantStructure, Genome, GeneticLineage};
+use crate::world::{Organ, VoxelWorld, VoxelType};
+
+/// Furthest a click can reach into the world (world units)
+const PICK_DISTANCE: f32 = 1000.0;
+
+/// Most branches listed in the inspect panel
+const MAX_LISTED_BRANCHES: usize = 8;
+
+/// Plugin for selecting a plant with the mouse and showing its details
+pub struct InspectPlugin;
+
+impl Plugin for InspectPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SelectedPlant::default())
+            .add_systems(Startup, setup_inspect_panel)
+            .add_systems(Update, (pick_plant_system, update_inspect_panel_system).chain());
+    }
+}
+
+/// Resource holding the plant shown in the inspect panel
+#[derive(Resource, Default)]
+pub struct SelectedPlant(pub Option<Entity>);
+
+/// Component marker for the inspect panel
+#[derive(Component)]
+pub struct InspectPanel;
+
+/// Component for the inspect panel text
+#[derive(Component)]
+pub struct InspectText;
+
+/// Setup the inspect panel, hidden until a plant is selected
+pub fn setup_inspect_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(60.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            Visibility::Hidden,
+            InspectPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                InspectText,
+            ));
+        });
+}
+
+/// Select the plant under the cursor on left click
+/// Clicking anything other than a plant clears the selection
+pub fn pick_plant_system(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<OrbitalCamera>>,
+    buttons: Query<&Interaction>,
+    plants: Query<Entity, With<PlantBiology>>,
+    world: Res<VoxelWorld>,
+    mut selected: ResMut<SelectedPlant>,
+) {
+    if !mouse_button.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    // Clicks on UI buttons are not meant for the world
+    if buttons.iter().any(|interaction| *interaction != Interaction::None) {
+        return;
+    }
+
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), cameras.single()) else {
+        return;
+    };
+    let Some(ray) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+
+    // Plant voxels store the index of their plant's entity
+    let plant_id = world
+        .raycast(ray.origin, *ray.direction, PICK_DISTANCE)
+        .and_then(|pos| match world.get(&pos)?.voxel_type {
+            VoxelType::PlantMaterial { plant_id, .. } => Some(plant_id),
+            _ => None,
+        });
+
+    selected.0 = plant_id.and_then(|id| plants.iter().find(|entity| entity.index() == id));
+}
+
+/// Show the selected plant's details, or hide the panel when nothing is selected
+pub fn update_inspect_panel_system(
+    mut selected: ResMut<SelectedPlant>,
+    plants: Query<(&PlantBiology, &PlantStructure, &Genome, &GeneticLineage)>,
+    mut panel_query: Query<&mut Visibility, With<InspectPanel>>,
+    mut text_query: Query<&mut Text, With<InspectText>>,
+) {
+    // The selected plant may have died since it was clicked
+    let details = selected.0.and_then(|entity| {
+        let (biology, structure, genome, lineage) = plants.get(entity).ok()?;
+        Some(plant_details(entity, biology, structure, genome, lineage))
+    });
+    if details.is_none() {
+        selected.0 = None;
+    }
+
+    for mut visibility in panel_query.iter_mut() {
+        *visibility = if details.is_some() { Visibility::Visible } else { Visibility::Hidden };
+    }
+    if let Some(details) = details {
+        for mut text in text_query.iter_mut() {
+            **text = details.clone();
+        }
+    }
+}
+
+/// Text describing a plant for the inspect panel
+pub fn plant_details(
+    entity: Entity,
+    biology: &PlantBiology,
+    structure: &PlantStructure,
+    genome: &Genome,
+    lineage: &GeneticLineage,
+) -> String {
+    let parent = match lineage.parent_id {
+        Some(parent) => format!("#{}", parent.index()),
+        None => "none".to_string(),
+    };
+
+    // Trait each gene produces, in the order of `Genome::GENE_NAMES`
+    let traits = [
+        format!("{:.2} voxels/s", genome.get_growth_rate()),
+        format!("{} voxels", genome.get_max_height()),
+        format!("{:.2} leaf chance", genome.get_leaf_density()),
+        format!("{} voxels", genome.get_root_depth()),
+        format!("{:.2} branch chance", genome.get_branching_frequency()),
+        format!("{:.2}x", genome.get_photosynthesis_efficiency()),
+        format!("{:.0} energy", genome.get_reproduction_threshold()),
+        format!("{:.3} per gene", genome.get_mutation_rate()),
+        format!("{:.2}", genome.get_horizontal_growth_tendency()),
+        format!("{:.1}°C", genome.get_cold_tolerance()),
+        format!("{:.2} of surplus", genome.get_storage_allocation()),
+        format!("{:.2}x strength", genome.get_wood_strength()),
+        format!("{:.2} of the year", genome.get_leaf_drop_time()),
+        format!("{:.2} of the year", genome.get_leaf_out_time()),
+    ];
+    // Stem branches, from the main stem outward
+    let stems: Vec<_> = structure
+        .branches()
+        .into_iter()
+        .filter(|branch| branch.organ == Organ::Stem)
+        .collect();
+    let mut branches: String = stems
+        .iter()
+        .take(MAX_LISTED_BRANCHES)
+        .map(|branch| {
+            format!(
+                "\nBranch {}: order {}, length {}, {} leaves",
+                branch.id, branch.order, branch.length, branch.leaves
+            )
+        })
+        .collect();
+    if stems.len() > MAX_LISTED_BRANCHES {
+        branches.push_str(&format!("\n...and {} more", stems.len() - MAX_LISTED_BRANCHES));
+    }
+
+    let leaves = structure.organ_positions(Organ::Leaf).count();
+    let leaves = if biology.dormant {
+        format!("{} (dormant, {} buds)", leaves, structure.leaf_buds.len())
+    } else {
+        leaves.to_string()
+    };
+
+    let genes: String = Genome::GENE_NAMES
+        .iter()
+        .zip(genome.gene_values())
+        .zip(traits)
+        .map(|((name, value), derived)| format!("\n{}: {:.2} ({})", name, value, derived))
+        .collect();
+
+    format!(
+        "Plant #{}\n\
+        \n\
+        Species: {}\n\
+        Generation: {}\n\
+        Parent: {}\n\
+        \n\
+        Energy: {:.1}\n\
+        Reserves: {:.1}\n\
+        Age: {:.1}s\n\
+        Mass: {} voxels\n\
+        Leaves: {}\n\
+        Roots: {}\n\
+        \n\
+        Branches: {}{}\n\
+        \n\
+        Genes:{}",
+        entity.index(),
+        lineage.species_id,
+        lineage.generation,
+        parent,
+        biology.energy,
+        biology.reserves,
+        biology.age,
+        biology.total_mass,
+        leaves,
+        structure.organ_positions(Organ::Root).count(),
+        stems.len(),
+        branches,
+        genes
+    )
+}
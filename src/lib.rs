@@ -0,0 +1,806 @@
+pub mod config;
+pub mod world;
+pub mod plant;
+pub mod camera;
+pub mod statistics;
+pub mod rendering;
+pub mod simulation;
+pub mod rng;
+pub mod headless;
+pub mod snapshot;
+pub mod inspect;
+
+pub use simulation::{SimulationPlugin, SimulationSet, SimulationState, SimulationClock};
+pub use rng::SimulationRng;
+pub use rendering::RenderingPlugin;
+pub use camera::CameraPlugin;
+pub use statistics::StatsUiPlugin;
+pub use snapshot::SnapshotPlugin;
+pub use inspect::InspectPlugin;
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::default;
+    use crate::config::*;
+    use crate::world::*;
+    use crate::plant::*;
+    use crate::statistics::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn test_core_initialization() {
+        // Test that core resources can be initialized
+        let world = VoxelWorld::new(WORLD_WIDTH, WORLD_HEIGHT, WORLD_DEPTH);
+        assert!(world.get(&VoxelPos::new(0, 0, 0)).is_some());
+
+        let day_night = DayNightCycle::default();
+        assert_eq!(day_night.time_of_day, 0.0);
+
+        let year_cycle = YearCycle::default();
+        assert_eq!(year_cycle.time_of_year, 0.0);
+
+        let stats = StatisticsHistory::default();
+        assert_eq!(stats.snapshots.len(), 0);
+
+        println!("Core initialization test passed!");
+    }
+
+    #[test]
+    fn test_plant_spawning_logic() {
+        // Test that we can create plant genome and components without full Bevy context
+        let mut rng = rand::rng();
+        let genome = Genome::random(&mut rng);
+
+        // Verify genome has valid values
+        assert!(genome.max_height.value > 0.0);
+        assert!(genome.leaf_density.value > 0.0);
+
+        println!("Plant spawning logic test passed!");
+    }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        // Fields missing from a config file should keep their default values
+        let config = SimConfig::from_toml("mutation_rate = 0.2\nworld_width = 32").unwrap();
+        assert_eq!(config.mutation_rate, 0.2);
+        assert_eq!(config.world_width, 32);
+        assert_eq!(config.world_height, WORLD_HEIGHT);
+        assert_eq!(config.base_maintenance_cost, BASE_MAINTENANCE_COST);
+
+        let world = VoxelWorld::from_config(&config, 0);
+        assert_eq!(world.width(), 32);
+    }
+
+    #[test]
+    fn test_terrain_surface_varies() {
+        // Noise terrain should produce hills and valleys within the world bounds
+        let config = SimConfig::default();
+        let world = VoxelWorld::from_config(&config, 1234);
+
+        let heights: Vec<i32> = (0..config.world_width as i32)
+            .map(|x| world.surface_height(x, 10).expect("Column has no soil"))
+            .collect();
+        let min = *heights.iter().min().unwrap();
+        let max = *heights.iter().max().unwrap();
+
+        assert!(max > min, "Terrain is flat");
+        assert!(max < config.world_height as i32 - 1, "Terrain leaves no room to grow");
+
+        // A flat world keeps its surface just below half height
+        let flat = VoxelWorld::new(WORLD_WIDTH, WORLD_HEIGHT, WORLD_DEPTH);
+        assert_eq!(flat.surface_height(5, 5), Some(WORLD_HEIGHT as i32 / 2 - 1));
+    }
+
+    #[test]
+    fn test_soil_fertility_varies() {
+        // Fertility patches should set different capacities and regeneration rates
+        let config = SimConfig::default();
+        let mut world = VoxelWorld::from_config(&config, 1234);
+
+        let soil: Vec<VoxelPos> = (0..config.world_width as i32)
+            .map(|x| VoxelPos::new(x, 0, 10))
+            .collect();
+        let fertility: Vec<f32> = soil
+            .iter()
+            .map(|pos| world.get(pos).unwrap().environment.fertility)
+            .collect();
+        let min = fertility.iter().cloned().fold(f32::MAX, f32::min);
+        let max = fertility.iter().cloned().fold(f32::MIN, f32::max);
+        assert!(max > min, "Fertility is uniform");
+
+        // Drain the soil, then regenerate once
+        for pos in &soil {
+            world.get_mut(pos).unwrap().environment.nutrients = 0.0;
+        }
+        let mut app = bevy::app::App::new();
+        app.insert_resource(world).insert_resource(config.clone());
+        app.world_mut().run_system_once(regenerate_resources_system).unwrap();
+
+        let world = app.world().resource::<VoxelWorld>();
+        for (pos, fertility) in soil.iter().zip(fertility) {
+            let nutrients = world.get(pos).unwrap().environment.nutrients;
+            assert!((nutrients - config.nutrient_regen_rate * fertility).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_rain_percolates_to_water_table() {
+        // Rain should soak in at the surface and sink, while deep ground stays saturated
+        let config = SimConfig {
+            water_table_depth: 4,
+            ..default()
+        };
+        let mut world = VoxelWorld::new(8, 16, 8);
+        for x in 0..8 {
+            for z in 0..8 {
+                for y in 0..8 {
+                    world.get_mut(&VoxelPos::new(x, y, z)).unwrap().environment.water = 0.0;
+                }
+            }
+        }
+
+        let mut app = bevy::app::App::new();
+        app.insert_resource(world)
+            .insert_resource(config.clone())
+            .insert_resource(Weather { raining: true, rain_remaining: 10.0 })
+            .insert_resource(DayNightCycle::default())
+            .insert_resource(YearCycle::default());
+        for _ in 0..20 {
+            app.world_mut().run_system_once(hydrology_system).unwrap();
+        }
+
+        let world = app.world().resource::<VoxelWorld>();
+        let water_at = |y| world.get(&VoxelPos::new(3, y, 3)).unwrap().environment.water;
+        assert!(water_at(7) > 0.0, "Rain did not reach the surface");
+        assert!(water_at(6) > 0.0, "Rain did not percolate downward");
+        assert_eq!(water_at(3), config.soil_water_max, "Water table is not saturated");
+    }
+
+    #[test]
+    fn test_dead_plant_decomposes_into_soil() {
+        // A dead plant's floating leaf should fall, and its litter should feed the soil below
+        let config = SimConfig::default();
+        let mut world = VoxelWorld::new(8, 16, 8);
+        let root = VoxelPos::new(3, 7, 3);
+        let leaf = VoxelPos::new(4, 12, 3);
+        world.get_mut(&VoxelPos::new(4, 7, 3)).unwrap().environment.nutrients = 0.0;
+
+        deposit_litter(&mut world, &[root, leaf], 40.0);
+
+        assert_eq!(world.get(&root).unwrap().voxel_type, VoxelType::Litter);
+        assert_eq!(world.get(&leaf).unwrap().voxel_type, VoxelType::Air);
+        let landed = VoxelPos::new(4, 8, 3);
+        assert_eq!(world.get(&landed).unwrap().voxel_type, VoxelType::Litter);
+        assert_eq!(world.get(&landed).unwrap().environment.nutrients, 20.0);
+
+        let mut app = bevy::app::App::new();
+        app.insert_resource(world).insert_resource(config);
+        for _ in 0..10 {
+            app.world_mut().run_system_once(decompose_litter_system).unwrap();
+        }
+
+        let world = app.world().resource::<VoxelWorld>();
+        let soil = world.get(&VoxelPos::new(4, 7, 3)).unwrap().environment.nutrients;
+        let litter = world.get(&landed).unwrap().environment.nutrients;
+        assert!(soil > 0.0, "Litter released no nutrients");
+        assert!((soil + litter - 20.0).abs() < 1e-3, "Nutrients were not conserved");
+    }
+
+    #[test]
+    fn test_temperature_and_cold_tolerance() {
+        // Winter and altitude should be colder, and cold tolerance should extend photosynthesis
+        let config = SimConfig::default();
+        let noon = DayNightCycle::default();
+        let summer = YearCycle { time_of_year: 0.375, ..default() };
+        let winter = YearCycle { time_of_year: 0.875, ..default() };
+        let summer_temp = get_base_temperature(&config, &noon, &summer);
+        let winter_temp = get_base_temperature(&config, &noon, &winter);
+        assert!(summer_temp > winter_temp + 20.0);
+
+        // A hill column should be colder than a valley column
+        let mut heights = vec![4; 8 * 8];
+        heights[0] = 12;
+        let world = VoxelWorld::from_heightmap(8, 16, 8, &heights);
+        let mut app = bevy::app::App::new();
+        app.insert_resource(world)
+            .insert_resource(config.clone())
+            .insert_resource(noon)
+            .insert_resource(summer)
+            .insert_resource(TemperatureField::default());
+        app.world_mut().run_system_once(update_temperature_system).unwrap();
+        let temperature = app.world().resource::<TemperatureField>();
+        assert!(temperature.get(0, 0) < temperature.get(1, 0));
+
+        // Just above freezing only a cold-tolerant plant can photosynthesize
+        let mut rng = rand::rng();
+        let mut hardy = Genome::random(&mut rng);
+        hardy.cold_tolerance = Gene::new(1.0);
+        let mut tender = hardy.clone();
+        tender.cold_tolerance = Gene::new(0.0);
+        assert_eq!(temperature_photosynthesis_factor(1.0, &tender, &config), 0.0);
+        assert!(temperature_photosynthesis_factor(1.0, &hardy, &config) > 0.0);
+    }
+
+    #[test]
+    fn test_tall_plant_casts_shadow_away_from_sun() {
+        // At summer noon the sun is high toward +Z, so a pillar shades the ground on its -Z side
+        let config = SimConfig::default();
+        let mut world = VoxelWorld::new(16, 32, 16);
+        for y in 16..26 {
+            world.get_mut(&VoxelPos::new(8, y, 8)).unwrap().voxel_type =
+                VoxelType::PlantMaterial { plant_id: 0, species_id: 0, organ: Organ::Stem };
+        }
+
+        let mut app = bevy::app::App::new();
+        app.insert_resource(world)
+            .insert_resource(config.clone())
+            .insert_resource(DayNightCycle { time_of_day: 0.25, ..default() })
+            .insert_resource(YearCycle { time_of_year: 0.375, ..default() });
+        app.world_mut().run_system_once(update_light_system).unwrap();
+
+        let world = app.world().resource::<VoxelWorld>();
+        let light_at = |z| world.get(&VoxelPos::new(8, 16, z)).unwrap().environment.light_level;
+        assert!((light_at(10) - config.sunlight_max).abs() < 1e-3, "Sunny side is shaded");
+        assert!(light_at(6) < config.sunlight_max * 0.9, "No shadow behind the pillar");
+
+        // At night only diffuse skylight remains
+        let mut app = bevy::app::App::new();
+        app.insert_resource(VoxelWorld::new(16, 32, 16))
+            .insert_resource(config.clone())
+            .insert_resource(DayNightCycle { time_of_day: 0.75, ..default() })
+            .insert_resource(YearCycle::default());
+        app.world_mut().run_system_once(update_light_system).unwrap();
+        let world = app.world().resource::<VoxelWorld>();
+        let night_light = world.get(&VoxelPos::new(8, 16, 8)).unwrap().environment.light_level;
+        assert!((night_light - config.sunlight_max * config.diffuse_light_fraction).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_incremental_light_matches_full_relight() {
+        // Relighting only changed columns should give the same light as relighting everything
+        let config = SimConfig::default();
+        let sun = DayNightCycle { time_of_day: 0.15, ..default() };
+        let light_levels = |world: &VoxelWorld| -> Vec<f32> {
+            world
+                .iter_positions()
+                .map(|pos| world.get(&pos).unwrap().environment.light_level)
+                .collect()
+        };
+
+        let mut app = bevy::app::App::new();
+        app.insert_resource(VoxelWorld::new(16, 32, 16))
+            .insert_resource(config.clone())
+            .insert_resource(sun.clone())
+            .insert_resource(YearCycle::default());
+        let system = app.world_mut().register_system(update_light_system);
+        app.world_mut().run_system(system).unwrap();
+
+        // Grow a pillar, then relight incrementally
+        {
+            let mut world = app.world_mut().resource_mut::<VoxelWorld>();
+            for y in 16..24 {
+                world.set_voxel_type(
+                    &VoxelPos::new(5, y, 9),
+                    VoxelType::PlantMaterial { plant_id: 0, species_id: 0, organ: Organ::Stem },
+                );
+            }
+        }
+        app.world_mut().run_system(system).unwrap();
+        let world = app.world().resource::<VoxelWorld>();
+        let incremental = light_levels(world);
+
+        // Relight a copy from scratch
+        let mut full_app = bevy::app::App::new();
+        full_app
+            .insert_resource(world.clone())
+            .insert_resource(config)
+            .insert_resource(sun)
+            .insert_resource(YearCycle::default());
+        full_app.world_mut().run_system_once(update_light_system).unwrap();
+
+        assert_eq!(incremental, light_levels(full_app.world().resource::<VoxelWorld>()));
+    }
+
+    #[test]
+    fn test_chunk_storage_and_dirty_tracking() {
+        // Dimensions that are not a multiple of CHUNK_SIZE leave partial chunks at the edges
+        let mut world = VoxelWorld::new(20, 40, 20);
+
+        // Soil fills the lower half, across chunk boundaries
+        assert_eq!(world.get(&VoxelPos::new(19, 19, 19)).unwrap().voxel_type, VoxelType::Soil);
+        assert_eq!(world.get(&VoxelPos::new(19, 20, 19)).unwrap().voxel_type, VoxelType::Air);
+        assert!(world.get(&VoxelPos::new(20, 0, 0)).is_none());
+        assert_eq!(world.ground_voxels_mut().count(), 20 * 20 * 20);
+
+        // Every chunk of a new world needs meshing, once
+        let chunk_count = 20usize.div_ceil(CHUNK_SIZE) * 40usize.div_ceil(CHUNK_SIZE) * 20usize.div_ceil(CHUNK_SIZE);
+        assert_eq!(world.take_dirty_chunks().len(), chunk_count);
+        assert!(world.take_dirty_chunks().is_empty());
+
+        // A voxel on a chunk face also dirties the chunk sharing that face
+        let pos = VoxelPos::new(CHUNK_SIZE as i32 - 1, 20, 3);
+        world.set_voxel_type(&pos, VoxelType::Litter);
+        assert_eq!(world.get(&pos).unwrap().voxel_type, VoxelType::Litter);
+
+        let mut dirty = world.take_dirty_chunks();
+        dirty.sort_by_key(|chunk| chunk.x);
+        assert_eq!(dirty, vec![ChunkPos::new(0, 1, 0), ChunkPos::new(1, 1, 0)]);
+    }
+
+    #[test]
+    fn test_greedy_meshing_covers_same_area() {
+        use crate::rendering::create_chunk_mesh;
+        use bevy::mesh::Mesh;
+
+        // Vertex count and total triangle area of a mesh
+        let measure = |mesh: &Mesh| -> (usize, f32) {
+            let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap().as_float3().unwrap();
+            let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+            let area = indices
+                .chunks(3)
+                .map(|tri| {
+                    let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| bevy::math::Vec3::from(positions[i]));
+                    (b - a).cross(c - a).length() / 2.0
+                })
+                .sum();
+            (positions.len(), area)
+        };
+
+        // A solid chunk of soil with one litter voxel on top, which must not merge with the soil around it
+        let mut world = VoxelWorld::new(CHUNK_SIZE, CHUNK_SIZE * 2, CHUNK_SIZE);
+        world.set_voxel_type(&VoxelPos::new(4, CHUNK_SIZE as i32 - 1, 4), VoxelType::Litter);
+        let chunk = ChunkPos::new(0, 0, 0);
+        let color = |voxel: &Voxel| voxel.voxel_type.get_color();
+
+        let (naive_vertices, naive_area) = measure(&create_chunk_mesh(&world, &chunk, MeshingMode::Naive, color).unwrap());
+        let (greedy_vertices, greedy_area) = measure(&create_chunk_mesh(&world, &chunk, MeshingMode::Greedy, color).unwrap());
+
+        // Every face of the cube is exposed: the top to air, the rest to the world edge
+        let face_area = VOXEL_SIZE * VOXEL_SIZE;
+        let exposed_faces = 6 * CHUNK_SIZE * CHUNK_SIZE;
+        assert_eq!(naive_vertices, exposed_faces * 4);
+        assert!((naive_area - exposed_faces as f32 * face_area).abs() < 1e-2);
+        assert!((greedy_area - naive_area).abs() < 1e-2);
+
+        // Five sides merge into one quad each; the top splits around the litter voxel into at most 5 quads
+        assert!(greedy_vertices <= (5 + 5) * 4, "greedy mesh has {greedy_vertices} vertices");
+
+        // An empty chunk has no mesh
+        assert!(create_chunk_mesh(&world, &ChunkPos::new(0, 1, 0), MeshingMode::Greedy, color).is_none());
+    }
+
+    #[test]
+    fn test_color_modes() {
+        use crate::rendering::{ColorMode, ColorSettings, VoxelColoring, chunk_color_fingerprint, plant_color_values};
+        use std::collections::HashMap;
+
+        let config = SimConfig::default();
+        let plant = |plant_id| Voxel {
+            voxel_type: VoxelType::PlantMaterial { plant_id, species_id: 1, organ: Organ::Leaf },
+            ..default()
+        };
+        let soil = |nutrients| {
+            let mut voxel = Voxel { voxel_type: VoxelType::Soil, ..default() };
+            voxel.environment.nutrients = nutrients;
+            voxel
+        };
+
+        // Species mode keeps the normal colours
+        let species = VoxelColoring::new(ColorMode::Species, &config, HashMap::new());
+        assert_eq!(species.color(&plant(1)), plant(1).voxel_type.get_color());
+        assert_eq!(species.color(&soil(0.0)), species.color(&soil(config.soil_nutrient_max)));
+
+        // Energy is relative to the most energetic plant, so the two plants differ
+        let mut world = bevy::ecs::world::World::new();
+        let low = world.spawn_empty().id();
+        let high = world.spawn_empty().id();
+        let lineage = GeneticLineage { generation: 0, parent_id: None, species_id: 1 };
+        let genome = Genome::random(&mut rand::rng());
+        let weak = PlantBiology { energy: 10.0, ..default() };
+        let strong = PlantBiology { energy: 200.0, ..default() };
+        let settings = ColorSettings { mode: ColorMode::Energy, gene: 0 };
+        let values = plant_color_values(
+            &settings,
+            [(low, &weak, &lineage, &genome), (high, &strong, &lineage, &genome)].into_iter(),
+        );
+        assert_eq!(values[&high.index()], 1.0);
+        assert!(values[&low.index()] < 0.1);
+
+        let energy = VoxelColoring::new(ColorMode::Energy, &config, values);
+        assert_ne!(energy.color(&plant(low.index())), energy.color(&plant(high.index())));
+        // Soil is not affected by plant modes
+        assert_eq!(energy.color(&soil(0.0)), VoxelType::Soil.get_color());
+
+        // Nutrient mode shades soil by its nutrients but leaves plants alone
+        let nutrients = VoxelColoring::new(ColorMode::Nutrients, &config, HashMap::new());
+        assert_ne!(nutrients.color(&soil(0.0)), nutrients.color(&soil(config.soil_nutrient_max)));
+        assert_eq!(nutrients.color(&plant(1)), plant(1).voxel_type.get_color());
+
+        // Drying one soil voxel recolours only its own chunk, and only in a mode that shows water
+        let mut voxels = VoxelWorld::new(2 * CHUNK_SIZE, CHUNK_SIZE, CHUNK_SIZE);
+        let (wet, dry) = (ChunkPos::new(0, 0, 0), ChunkPos::new(1, 0, 0));
+        let water = VoxelColoring::new(ColorMode::Water, &config, HashMap::new());
+        let fingerprints = |voxels: &VoxelWorld, coloring: &VoxelColoring| {
+            [wet, dry].map(|chunk| chunk_color_fingerprint(voxels, &chunk, |voxel| coloring.color(voxel)))
+        };
+        let (water_before, species_before) = (fingerprints(&voxels, &water), fingerprints(&voxels, &species));
+        voxels.get_mut(&VoxelPos::new(2, 2, 2)).unwrap().environment.water = 0.0;
+        let (water_after, species_after) = (fingerprints(&voxels, &water), fingerprints(&voxels, &species));
+        assert_ne!(water_after[0], water_before[0]);
+        assert_eq!(water_after[1], water_before[1]);
+        assert_eq!(species_after, species_before);
+
+        // Cycling through every mode returns to the start
+        let mut mode = ColorMode::Species;
+        for _ in 0..8 {
+            mode = mode.next();
+        }
+        assert_eq!(mode, ColorMode::Species);
+    }
+
+    #[test]
+    fn test_raycast_picks_first_solid_voxel() {
+        use bevy::math::Vec3;
+
+        let mut world = VoxelWorld::new(16, 32, 16);
+        let stem = VoxelPos::new(8, 20, 8);
+        world.set_voxel_type(&stem, VoxelType::PlantMaterial { plant_id: 7, species_id: 1, organ: Organ::Stem });
+
+        // Looking down from above the world hits the top of the stem, not the soil below it
+        let above = VoxelPos::new(8, 40, 8).to_world_pos();
+        assert_eq!(world.raycast(above, Vec3::NEG_Y, 1000.0), Some(stem));
+
+        // A slanted ray from outside the world passes the stem and lands on the surface
+        let hit = world.raycast(VoxelPos::new(-10, 30, 3).to_world_pos(), Vec3::new(1.0, -1.0, 0.2), 1000.0).unwrap();
+        assert_eq!(hit.y, 15);
+
+        // Rays pointing away from the world, or too short to reach it, hit nothing
+        assert_eq!(world.raycast(above, Vec3::Y, 1000.0), None);
+        assert_eq!(world.raycast(above, Vec3::NEG_Y, VOXEL_SIZE * 5.0), None);
+    }
+
+    #[test]
+    fn test_plant_organs() {
+        let config = SimConfig::default();
+        let organ = |organ| VoxelType::PlantMaterial { plant_id: 0, species_id: 3, organ };
+        let organs = [Organ::Stem, Organ::Leaf, Organ::Root, Organ::Flower, Organ::Seed, Organ::Storage];
+
+        // Leaves shade more than stems, and buried organs block light like soil
+        assert!(organ(Organ::Leaf).light_transmission() < organ(Organ::Stem).light_transmission());
+        assert_eq!(organ(Organ::Root).light_transmission(), VoxelType::Soil.light_transmission());
+
+        // Every organ has its own colour
+        for (i, a) in organs.iter().enumerate() {
+            for b in &organs[i + 1..] {
+                assert_ne!(organ(*a).get_color(), organ(*b).get_color(), "{a:?} and {b:?} look the same");
+            }
+        }
+
+        // Leaves cost more upkeep than woody stems, and dormant seeds almost nothing
+        assert!(organ_maintenance_factor(Organ::Leaf, &config) > organ_maintenance_factor(Organ::Stem, &config));
+        assert!(organ_maintenance_factor(Organ::Seed, &config) < organ_maintenance_factor(Organ::Root, &config));
+
+        // A new plant starts as a seed in the surface soil
+        let mut app = bevy::app::App::new();
+        app.insert_resource(VoxelWorld::new(16, 32, 16));
+        let pos = VoxelPos::new(8, 15, 8);
+        app.world_mut()
+            .run_system_once(
+                move |mut commands: bevy::prelude::Commands, mut world: bevy::prelude::ResMut<VoxelWorld>| {
+                    spawn_plant(&mut commands, &mut world, pos, Genome::random(&mut rand::rng()), 0, None, 3);
+                },
+            )
+            .unwrap();
+        let world = app.world().resource::<VoxelWorld>();
+        assert_eq!(world.get(&pos).unwrap().voxel_type.organ(), Some(Organ::Seed));
+    }
+
+    #[test]
+    fn test_flowering_plant_keeps_growing() {
+        // A young plant with a two-voxel stem and enough energy to reproduce
+        let base = VoxelPos::new(8, 15, 8);
+        let top = VoxelPos::new(8, 17, 8);
+        let mut world = VoxelWorld::new(16, 32, 16);
+        let mut structure = PlantStructure::new(base);
+        for y in 16..=17 {
+            let pos = VoxelPos::new(8, y, 8);
+            structure.add_segment(pos, Organ::Stem, VoxelPos::new(8, y - 1, 8));
+            world.set_voxel_type(&pos, VoxelType::PlantMaterial { plant_id: 0, species_id: 0, organ: Organ::Stem });
+        }
+        let mut genome = Genome::random(&mut rand::rng());
+        genome.max_height.value = 1.0;
+        genome.horizontal_growth_tendency.value = 0.0;
+        genome.branching_frequency.value = 0.0;
+        let lineage = GeneticLineage { generation: 0, parent_id: None, species_id: 0 };
+
+        let mut app = bevy::app::App::new();
+        app.insert_resource(world)
+            .insert_resource(SimConfig::default())
+            .insert_resource(SpeciesCounter::default())
+            .insert_resource(crate::SimulationRng::new(7))
+            .insert_resource(bevy::prelude::Time::<()>::default());
+        let biology = PlantBiology { energy: 1000.0, ..default() };
+        let plant = app
+            .world_mut()
+            .spawn((biology, structure, genome, lineage, GrowthTimer::default()))
+            .id();
+
+        // The plant flowers beside its stem rather than on its tip
+        app.world_mut().run_system_once(reproduction_system).unwrap();
+        let structure = app.world().get::<PlantStructure>(plant).unwrap();
+        let flower = structure.organ_positions(Organ::Flower).next().expect("Plant did not flower");
+        assert_eq!(flower.y, top.y);
+
+        // Setting seed uses up the flower, so the next reproduction has to wait for a new bloom
+        app.world_mut().run_system_once(reproduction_system).unwrap();
+        let structure = app.world().get::<PlantStructure>(plant).unwrap();
+        assert_eq!(structure.organ_positions(Organ::Flower).count(), 0);
+        assert_eq!(structure.segment(&flower).unwrap().organ, Organ::Seed);
+        let world = app.world().resource::<VoxelWorld>();
+        assert_eq!(world.get(&flower).unwrap().voxel_type.organ(), Some(Organ::Seed));
+
+        app.world_mut().run_system_once(reproduction_system).unwrap();
+        let structure = app.world().get::<PlantStructure>(plant).unwrap();
+        assert_eq!(structure.organ_positions(Organ::Flower).count(), 1);
+        assert_ne!(structure.organ_positions(Organ::Flower).next(), Some(flower));
+
+        // Growth carries on upward past the flower
+        for _ in 0..10 {
+            app.world_mut()
+                .resource_mut::<bevy::prelude::Time>()
+                .advance_by(std::time::Duration::from_secs(1));
+            app.world_mut().run_system_once(plant_growth_system).unwrap();
+        }
+        let structure = app.world().get::<PlantStructure>(plant).unwrap();
+        assert!(
+            structure.organ_positions(Organ::Stem).any(|pos| pos.y > top.y),
+            "Flowered plant stopped growing upward"
+        );
+    }
+
+    #[test]
+    fn test_energy_transport_and_storage() {
+        let config = SimConfig::default();
+        let delta = 0.1;
+        let loss = 1.0 - config.transport_loss;
+
+        // A stem rising from the base with a leaf at the top
+        let base = VoxelPos::new(4, 10, 4);
+        let mut structure = PlantStructure::new(base);
+        for y in 11..16 {
+            structure.add_segment(VoxelPos::new(4, y, 4), Organ::Stem, VoxelPos::new(4, y - 1, 4));
+        }
+        let leaf = VoxelPos::new(5, 15, 4);
+        structure.add_segment(leaf, Organ::Leaf, VoxelPos::new(4, 15, 4));
+        let detached = VoxelPos::new(9, 15, 9);
+        let network = TransportNetwork::new(&structure);
+
+        // Energy loses a little for every voxel it passes on the way down
+        assert_eq!(network.distance(&leaf), Some(6));
+        let delivered = network.deliver(&[(leaf, 1.0)], &config, delta);
+        assert!((delivered - loss.powi(6)).abs() < 1e-5);
+
+        // Nothing arrives from a voxel that is not part of the plant
+        assert_eq!(network.distance(&detached), None);
+        assert_eq!(network.deliver(&[(detached, 1.0)], &config, delta), 0.0);
+
+        // The stem carries at most its capacity, however much the leaves make
+        let flood = config.transport_capacity * delta * 10.0;
+        let delivered = network.deliver(&[(leaf, flood), (VoxelPos::new(4, 15, 4), flood)], &config, delta);
+        assert!(delivered <= config.transport_capacity * delta + 1e-3);
+
+        // The base is no exception: energy made there still has to fit through it
+        let delivered = network.deliver(&[(base, flood), (leaf, flood)], &config, delta);
+        assert!(delivered <= config.transport_capacity * delta + 1e-3);
+
+        // Surplus energy is banked, up to the storage organs' capacity
+        let mut biology = PlantBiology { energy: config.storage_fill_energy + 50.0, ..default() };
+        exchange_reserves(&mut biology, 2, 1.0, 1.0, &config, 1.0);
+        assert!((biology.reserves - 2.0 * config.storage_rate).abs() < 1e-4);
+        assert!((biology.energy + biology.reserves - (config.storage_fill_energy + 50.0)).abs() < 1e-4);
+
+        // Low energy draws reserves back, minus the transport loss
+        let mut hungry = PlantBiology { energy: 0.0, reserves: 50.0, ..default() };
+        exchange_reserves(&mut hungry, 2, 1.0, 1.0, &config, 1.0);
+        let drawn = 2.0 * config.storage_rate;
+        assert!((hungry.reserves - (50.0 - drawn)).abs() < 1e-4);
+        assert!((hungry.energy - drawn * loss).abs() < 1e-4);
+
+        // Reserves in lost storage organs are gone
+        exchange_reserves(&mut hungry, 0, 0.0, 1.0, &config, 1.0);
+        assert_eq!(hungry.reserves, 0.0);
+    }
+
+    #[test]
+    fn test_branch_graph_topology() {
+        // A main stem four voxels tall, with a side branch forking from its second voxel
+        let base = VoxelPos::new(4, 10, 4);
+        let mut structure = PlantStructure::new(base);
+        for y in 11..15 {
+            assert!(structure.add_segment(VoxelPos::new(4, y, 4), Organ::Stem, VoxelPos::new(4, y - 1, 4)));
+        }
+        let fork = VoxelPos::new(4, 12, 4);
+        let side = [VoxelPos::new(5, 12, 4), VoxelPos::new(6, 12, 4)];
+        structure.add_segment(side[0], Organ::Stem, fork);
+        structure.add_segment(side[1], Organ::Stem, side[0]);
+        structure.add_segment(VoxelPos::new(6, 13, 4), Organ::Leaf, side[1]);
+        structure.add_segment(VoxelPos::new(3, 14, 4), Organ::Leaf, VoxelPos::new(4, 14, 4));
+        structure.add_segment(VoxelPos::new(4, 9, 4), Organ::Root, base);
+
+        // Segments can only grow from the plant
+        assert!(!structure.add_segment(VoxelPos::new(0, 0, 0), Organ::Stem, VoxelPos::new(1, 1, 1)));
+        // A voxel already in the plant cannot be added again
+        let plant_len = structure.len();
+        assert!(!structure.add_segment(fork, Organ::Leaf, VoxelPos::new(4, 14, 4)));
+        assert_eq!(structure.len(), plant_len);
+        assert_eq!(structure.segment(&fork).unwrap().organ, Organ::Stem);
+
+        // The main stem and the side branch are tracked separately, each with one growing tip
+        let stems: Vec<BranchMetrics> = structure
+            .branches()
+            .into_iter()
+            .filter(|branch| branch.organ == Organ::Stem)
+            .collect();
+        assert_eq!(stems.len(), 2);
+        assert_eq!((stems[0].order, stems[0].length, stems[0].leaves), (0, 4, 1));
+        assert_eq!((stems[1].order, stems[1].length, stems[1].leaves), (1, 2, 1));
+        assert_eq!(structure.tips(Organ::Stem), vec![VoxelPos::new(4, 14, 4), side[1]]);
+        assert_eq!(structure.branch_of(&side[0]).unwrap().id, stems[1].id);
+
+        // Losing the fork point prunes everything above it, but leaves the base and roots
+        let removed = structure.remove_segment(&fork);
+        assert_eq!(removed.len(), 7);
+        assert_eq!(structure.len(), 3);
+        assert!(structure.segment(&VoxelPos::new(4, 11, 4)).is_some());
+        assert!(structure.segment(&VoxelPos::new(4, 9, 4)).is_some());
+        assert_eq!(structure.organ_positions(Organ::Leaf).count(), 0);
+        assert_eq!(structure.tips(Organ::Stem), vec![VoxelPos::new(4, 11, 4)]);
+        assert_eq!(structure.tip(stems[0].id), Some(VoxelPos::new(4, 11, 4)));
+        assert!(structure.segment(&fork).is_none());
+    }
+
+    #[test]
+    fn test_structural_support() {
+        // A trunk rising from the base with an arm reaching seven voxels east
+        let base = VoxelPos::new(8, 15, 8);
+        let trunk_top = VoxelPos::new(8, 18, 8);
+        let mut structure = PlantStructure::new(base);
+        for y in 16..=18 {
+            structure.add_segment(VoxelPos::new(8, y, 8), Organ::Stem, VoxelPos::new(8, y - 1, 8));
+        }
+        let grow_arm = |structure: &mut PlantStructure, step: i32| {
+            for i in 1..=7 {
+                let parent = VoxelPos::new(8 + (i - 1) * step, 18, 8);
+                structure.add_segment(VoxelPos::new(8 + i * step, 18, 8), Organ::Stem, parent);
+            }
+        };
+        grow_arm(&mut structure, 1);
+
+        // The arm's weight bends the trunk by its distance out; the arm's tip carries nothing
+        let loads = segment_loads(&structure);
+        assert_eq!(loads[&trunk_top], SegmentLoad { weight: 7.0, moment: 28.0 });
+        assert_eq!(loads[&VoxelPos::new(8, 16, 8)], SegmentLoad { weight: 9.0, moment: 28.0 });
+        assert_eq!(loads[&VoxelPos::new(15, 18, 8)], SegmentLoad::default());
+
+        // A matching arm to the west balances the trunk
+        grow_arm(&mut structure, -1);
+        let loads = segment_loads(&structure);
+        assert_eq!(loads[&trunk_top], SegmentLoad { weight: 14.0, moment: 0.0 });
+
+        // With weak wood both arms snap at the shoulder and fall as litter, while the trunk stands
+        let config = SimConfig { stem_strength: 40.0, ..default() };
+        let mut genome = Genome::random(&mut rand::rng());
+        genome.wood_density.value = 0.0;
+        let mut app = bevy::app::App::new();
+        app.insert_resource(VoxelWorld::new(16, 32, 16)).insert_resource(config);
+        let plant = app.world_mut().spawn((PlantBiology::default(), structure, genome)).id();
+        app.world_mut().run_system_once(structural_support_system).unwrap();
+
+        let structure = app.world().get::<PlantStructure>(plant).unwrap();
+        assert_eq!(structure.len(), 4);
+        assert!(structure.segment(&trunk_top).is_some());
+        assert_eq!(app.world().get::<PlantBiology>(plant).unwrap().total_mass, 4);
+        let world = app.world().resource::<VoxelWorld>();
+        assert_eq!(world.get(&VoxelPos::new(12, 16, 8)).unwrap().voxel_type, VoxelType::Litter);
+        assert_eq!(world.get(&VoxelPos::new(4, 16, 8)).unwrap().voxel_type, VoxelType::Litter);
+
+        // A straight trunk bends nothing, but grown too tall its own weight buckles it at the base
+        let base = VoxelPos::new(3, 15, 3);
+        let mut tall = PlantStructure::new(base);
+        for y in 16..=25 {
+            tall.add_segment(VoxelPos::new(3, y, 3), Organ::Stem, VoxelPos::new(3, y - 1, 3));
+        }
+        assert_eq!(segment_loads(&tall)[&VoxelPos::new(3, 16, 3)], SegmentLoad { weight: 9.0, moment: 0.0 });
+        let mut genome = Genome::random(&mut rand::rng());
+        genome.wood_density.value = 0.0;
+        app.world_mut().resource_mut::<SimConfig>().stem_compression_strength = 10.0;
+        let tall = app.world_mut().spawn((PlantBiology::default(), tall, genome)).id();
+        app.world_mut().run_system_once(structural_support_system).unwrap();
+
+        assert_eq!(app.world().get::<PlantStructure>(tall).unwrap().len(), 1);
+        assert_eq!(app.world().get::<PlantStructure>(plant).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_deciduous_phenology() {
+        // A deciduous plant that sheds at the start of autumn, and an evergreen, each with two leaves
+        let config = SimConfig::default();
+        let mut world = VoxelWorld::new(16, 32, 16);
+        let mut app = bevy::app::App::new();
+        let mut plants = Vec::new();
+        for (x, leaf_drop) in [(4, 0.0), (11, 1.0)] {
+            let mut structure = PlantStructure::new(VoxelPos::new(x, 15, 8));
+            structure.add_segment(VoxelPos::new(x, 16, 8), Organ::Stem, VoxelPos::new(x, 15, 8));
+            structure.add_segment(VoxelPos::new(x, 17, 8), Organ::Stem, VoxelPos::new(x, 16, 8));
+            for leaf in [VoxelPos::new(x - 1, 17, 8), VoxelPos::new(x + 1, 17, 8)] {
+                structure.add_segment(leaf, Organ::Leaf, VoxelPos::new(x, 17, 8));
+                world.set_voxel_type(&leaf, VoxelType::PlantMaterial { plant_id: 0, species_id: 0, organ: Organ::Leaf });
+            }
+
+            let mut genome = Genome::random(&mut rand::rng());
+            genome.leaf_drop.value = leaf_drop;
+            genome.leaf_out.value = 0.0;
+            let lineage = GeneticLineage { generation: 0, parent_id: None, species_id: 0 };
+            plants.push(app.world_mut().spawn((PlantBiology::default(), structure, genome, lineage)).id());
+        }
+        app.insert_resource(world)
+            .insert_resource(config)
+            .insert_resource(YearCycle { time_of_year: 0.6, ..default() });
+
+        // In autumn the deciduous plant drops its leaves as litter and keeps buds for spring
+        app.world_mut().run_system_once(phenology_system).unwrap();
+        let (deciduous, evergreen) = (plants[0], plants[1]);
+        let structure = app.world().get::<PlantStructure>(deciduous).unwrap();
+        assert!(app.world().get::<PlantBiology>(deciduous).unwrap().dormant);
+        assert_eq!(structure.organ_positions(Organ::Leaf).count(), 0);
+        assert_eq!(structure.leaf_buds.len(), 2);
+
+        // Shedding again keeps the buds that have not leafed out, without doubling any up
+        let mut reshed = structure.clone();
+        reshed.add_segment(VoxelPos::new(3, 17, 8), Organ::Leaf, VoxelPos::new(4, 17, 8));
+        reshed.add_segment(VoxelPos::new(4, 17, 9), Organ::Leaf, VoxelPos::new(4, 17, 8));
+        reshed.shed_leaves();
+        assert_eq!(reshed.leaf_buds.len(), 3);
+        let world = app.world().resource::<VoxelWorld>();
+        assert!(world.get(&VoxelPos::new(5, 17, 8)).unwrap().voxel_type.is_air());
+        assert_eq!(world.get(&VoxelPos::new(5, 16, 8)).unwrap().voxel_type, VoxelType::Litter);
+
+        // The evergreen keeps its leaves
+        assert!(!app.world().get::<PlantBiology>(evergreen).unwrap().dormant);
+        assert_eq!(app.world().get::<PlantStructure>(evergreen).unwrap().organ_positions(Organ::Leaf).count(), 2);
+
+        // In spring the deciduous plant pays to regrow its leaves where they were
+        app.world_mut().resource_mut::<YearCycle>().time_of_year = 0.1;
+        app.world_mut().run_system_once(phenology_system).unwrap();
+        let biology = app.world().get::<PlantBiology>(deciduous).unwrap();
+        let structure = app.world().get::<PlantStructure>(deciduous).unwrap();
+        assert!(!biology.dormant);
+        assert!(biology.energy < PlantBiology::default().energy);
+        assert_eq!(structure.organ_positions(Organ::Leaf).count(), 2);
+        assert!(structure.leaf_buds.is_empty());
+        let world = app.world().resource::<VoxelWorld>();
+        assert_eq!(world.get(&VoxelPos::new(5, 17, 8)).unwrap().voxel_type.organ(), Some(Organ::Leaf));
+    }
+
+    #[test]
+    fn test_time_scale_limits() {
+        // Speed changes should stay within the configured range
+        let mut state = crate::SimulationState::default();
+        for _ in 0..20 {
+            state.speed_up();
+        }
+        assert_eq!(state.time_scale, MAX_TIME_SCALE);
+
+        for _ in 0..20 {
+            state.slow_down();
+        }
+        assert_eq!(state.time_scale, MIN_TIME_SCALE);
+
+        // Stepping only applies while paused
+        state.step();
+        assert!(!state.step_requested);
+        state.paused = true;
+        state.step();
+        assert!(state.step_requested);
+    }
+}
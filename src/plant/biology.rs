@@ -1,15 +1,22 @@
 use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use crate::config::*;
-use crate::world::{VoxelWorld, VoxelPos, VoxelType, YearCycle, get_seasonal_multiplier};
+use crate::rng::SimulationRng;
+use crate::world::{Organ, VoxelWorld, VoxelPos, VoxelType, YearCycle, TemperatureField, get_seasonal_multiplier, deposit_litter};
 use super::genetics::Genome;
+use super::structure::PlantStructure;
+use super::transport::{TransportNetwork, exchange_reserves};
 
 /// Component to track plant's energy and state
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct PlantBiology {
     pub energy: f32,
     pub age: f32, // In seconds
     pub is_alive: bool,
     pub total_mass: u32, // Number of voxels
+    pub reserves: f32, // Energy banked in storage organs
+    pub dormant: bool, // Leafless for winter, waiting to leaf out in spring
 }
 
 impl Default for PlantBiology {
@@ -19,35 +26,40 @@ impl Default for PlantBiology {
             age: 0.0,
             is_alive: true,
             total_mass: 0,
+            reserves: 0.0,
+            dormant: false,
         }
     }
 }
 
-/// Component to track plant structure in the world
-#[derive(Component, Debug)]
-pub struct PlantStructure {
-    pub root_position: VoxelPos,
-    pub voxel_positions: Vec<VoxelPos>,
-    pub leaf_positions: Vec<VoxelPos>,
-    pub root_positions: Vec<VoxelPos>,
-}
+/// Photosynthesis rate multiplier (0.0 to 1.0) at a temperature
+/// Rises from the plant's cold limit to the optimum, then slowly falls off in heat
+pub fn temperature_photosynthesis_factor(temperature: f32, genome: &Genome, config: &SimConfig) -> f32 {
+    let min = config.photosynthesis_min_temperature - genome.get_cold_tolerance();
+    let optimum = config.photosynthesis_optimal_temperature;
 
-impl PlantStructure {
-    pub fn new(root: VoxelPos) -> Self {
-        Self {
-            root_position: root,
-            voxel_positions: vec![root],
-            leaf_positions: Vec::new(),
-            root_positions: vec![root],
-        }
+    if temperature <= optimum {
+        ((temperature - min) / (optimum - min)).clamp(0.0, 1.0)
+    } else {
+        // Lose 2% per degree above the optimum, down to half rate
+        (1.0 - (temperature - optimum) * 0.02).max(0.5)
     }
 }
 
+/// Maintenance cost multiplier at a temperature (respiration speeds up as it warms)
+pub fn temperature_maintenance_factor(temperature: f32, config: &SimConfig) -> f32 {
+    config
+        .maintenance_q10
+        .powf((temperature - config.photosynthesis_optimal_temperature) / 10.0)
+}
+
 /// System to perform photosynthesis for all plants
 pub fn photosynthesis_system(
     mut plants: Query<(&mut PlantBiology, &PlantStructure, &Genome)>,
     world: Res<VoxelWorld>,
     year_cycle: Res<YearCycle>,
+    temperature: Res<TemperatureField>,
+    config: Res<SimConfig>,
     time: Res<Time>,
 ) {
     // Get seasonal multiplier - this creates the harsh winter bottleneck
@@ -58,20 +70,29 @@ pub fn photosynthesis_system(
             continue;
         }
 
-        let mut total_energy_gain = 0.0;
-
-        // Calculate energy from each leaf
-        for leaf_pos in &structure.leaf_positions {
-            if let Some(voxel) = world.get(leaf_pos) {
+        // Energy made by each leaf
+        let mut produced = Vec::new();
+        for leaf_pos in structure.organ_positions(Organ::Leaf) {
+            if let Some(voxel) = world.get(&leaf_pos) {
                 let light = voxel.environment.light_level;
                 let efficiency = genome.get_photosynthesis_efficiency();
+                // Cold slows photosynthesis unless the plant is cold tolerant
+                let temperature_factor = temperature_photosynthesis_factor(
+                    temperature.get(leaf_pos.x, leaf_pos.z),
+                    genome,
+                    &config,
+                );
                 // Apply seasonal multiplier - during winter (5%), plants get almost no energy
                 // and must survive on their energy reserves
-                total_energy_gain += light * PHOTOSYNTHESIS_EFFICIENCY * efficiency * seasonal_multiplier * time.delta_secs();
+                let energy = light * config.photosynthesis_efficiency * efficiency * seasonal_multiplier
+                    * temperature_factor * time.delta_secs();
+                produced.push((leaf_pos, energy));
             }
         }
 
-        biology.energy += total_energy_gain;
+        // Only what makes it down the stems reaches the plant's energy
+        let network = TransportNetwork::new(structure);
+        biology.energy += network.deliver(&produced, &config, time.delta_secs());
     }
 }
 
@@ -79,6 +100,7 @@ pub fn photosynthesis_system(
 pub fn resource_absorption_system(
     mut plants: Query<(&mut PlantBiology, &PlantStructure)>,
     mut world: ResMut<VoxelWorld>,
+    config: Res<SimConfig>,
     time: Res<Time>,
 ) {
     for (mut biology, structure) in plants.iter_mut() {
@@ -86,50 +108,114 @@ pub fn resource_absorption_system(
             continue;
         }
 
-        let mut nutrients_absorbed = 0.0;
-        let mut water_absorbed = 0.0;
+        // Energy gained at each root
+        let mut absorbed = Vec::new();
 
         // Absorb from each root position
-        for root_pos in &structure.root_positions {
-            if let Some(voxel) = world.get_mut(root_pos) {
-                let absorption_rate = ROOT_ABSORPTION_RATE * time.delta_secs();
+        for root_pos in structure.organ_positions(Organ::Root) {
+            if let Some(voxel) = world.get_mut(&root_pos) {
+                let absorption_rate = config.root_absorption_rate * time.delta_secs();
 
                 // Try to absorb nutrients
                 let nutrients = absorption_rate.min(voxel.environment.nutrients);
                 voxel.environment.nutrients -= nutrients;
-                nutrients_absorbed += nutrients;
 
                 // Try to absorb water
                 let water = absorption_rate.min(voxel.environment.water);
                 voxel.environment.water -= water;
-                water_absorbed += water;
+
+                // Convert resources to energy (simplified)
+                absorbed.push((root_pos, (nutrients + water) * 0.1));
             }
         }
 
-        // Convert resources to energy (simplified)
-        biology.energy += (nutrients_absorbed + water_absorbed) * 0.1;
+        let network = TransportNetwork::new(structure);
+        biology.energy += network.deliver(&absorbed, &config, time.delta_secs());
+    }
+}
+
+/// System to bank surplus energy in storage organs and draw on it when energy runs low
+/// Reserves carry plants through winters and dry spells
+pub fn storage_system(
+    mut plants: Query<(&mut PlantBiology, &PlantStructure, &Genome)>,
+    config: Res<SimConfig>,
+    time: Res<Time>,
+) {
+    for (mut biology, structure, genome) in plants.iter_mut() {
+        if !biology.is_alive {
+            continue;
+        }
+
+        // Reserves are held in the plant's storage organs, some way from the base
+        let network = TransportNetwork::new(structure);
+        let distances: Vec<usize> = structure
+            .organ_positions(Organ::Storage)
+            .filter_map(|pos| network.distance(&pos))
+            .collect();
+        let mean_distance = distances.iter().sum::<usize>() as f32 / distances.len().max(1) as f32;
+
+        exchange_reserves(
+            &mut biology,
+            distances.len(),
+            mean_distance,
+            genome.get_storage_allocation(),
+            &config,
+            time.delta_secs(),
+        );
+    }
+}
+
+/// Maintenance cost multiplier for an organ
+pub fn organ_maintenance_factor(organ: Organ, config: &SimConfig) -> f32 {
+    match organ {
+        Organ::Stem => config.stem_maintenance,
+        Organ::Leaf => config.leaf_maintenance,
+        Organ::Root => config.root_maintenance,
+        Organ::Flower => config.flower_maintenance,
+        Organ::Seed => config.seed_maintenance,
+        Organ::Storage => config.storage_maintenance,
     }
 }
 
 /// System to consume energy for maintenance
 pub fn maintenance_cost_system(
-    mut plants: Query<(&mut PlantBiology, &PlantStructure)>,
+    mut plants: Query<(&mut PlantBiology, &PlantStructure, &Genome)>,
+    temperature: Res<TemperatureField>,
+    config: Res<SimConfig>,
     time: Res<Time>,
 ) {
-    for (mut biology, structure) in plants.iter_mut() {
+    for (mut biology, structure, genome) in plants.iter_mut() {
         if !biology.is_alive {
             continue;
         }
 
+        // Warmth speeds up respiration, and cold tolerance has an upkeep cost
+        let root = structure.root_position;
+        let temperature_factor = temperature_maintenance_factor(temperature.get(root.x, root.z), &config);
+        let cold_tolerance_factor = 1.0 + genome.cold_tolerance.value * config.cold_tolerance_cost;
+        let wood_factor = 1.0 + genome.wood_density.value * config.wood_cost;
+
+        // Each organ costs a different amount to keep alive, and dense wood costs more
+        let organ_voxels: f32 = structure
+            .segments()
+            .iter()
+            .map(|segment| match segment.organ {
+                Organ::Stem => organ_maintenance_factor(Organ::Stem, &config) * wood_factor,
+                organ => organ_maintenance_factor(organ, &config),
+            })
+            .sum();
+
         // Calculate base maintenance cost
-        let base_maintenance = structure.voxel_positions.len() as f32
-            * BASE_MAINTENANCE_COST
+        let base_maintenance = organ_voxels
+            * config.base_maintenance_cost
+            * temperature_factor
+            * cold_tolerance_factor
             * time.delta_secs();
 
         // Add gravity-based transport cost - higher voxels cost more energy
         let root_height = structure.root_position.y;
         let mut gravity_cost = 0.0;
-        for voxel_pos in &structure.voxel_positions {
+        for voxel_pos in structure.positions() {
             // Height difference from root (in voxels)
             let height_diff = (voxel_pos.y - root_height).max(0) as f32;
             // Energy cost increases with height (0.05 energy per voxel per unit height)
@@ -148,6 +234,49 @@ pub fn maintenance_cost_system(
     }
 }
 
+/// System to kill leaves exposed to frost
+/// Only leaves open to the sky are exposed; the colder it is below the plant's frost limit,
+/// the more likely each leaf dies and drops as litter
+pub fn frost_damage_system(
+    mut plants: Query<(&mut PlantBiology, &mut PlantStructure, &Genome)>,
+    mut world: ResMut<VoxelWorld>,
+    temperature: Res<TemperatureField>,
+    mut sim_rng: ResMut<SimulationRng>,
+    config: Res<SimConfig>,
+    time: Res<Time>,
+) {
+    let rng = &mut **sim_rng;
+
+    for (mut biology, mut structure, genome) in plants.iter_mut() {
+        if !biology.is_alive {
+            continue;
+        }
+
+        let frost_limit = config.frost_temperature - genome.get_cold_tolerance();
+        let frozen: Vec<VoxelPos> = structure
+            .organ_positions(Organ::Leaf)
+            .filter(|pos| {
+                let frost = frost_limit - temperature.get(pos.x, pos.z);
+                frost > 0.0
+                    && world.is_open_to_sky(pos)
+                    && rng.random::<f32>() < frost * config.frost_damage_rate * time.delta_secs()
+            })
+            .collect();
+
+        if frozen.is_empty() {
+            continue;
+        }
+
+        let killed: Vec<VoxelPos> = frozen.iter().flat_map(|pos| structure.remove_segment(pos)).collect();
+        biology.total_mass = structure.len() as u32;
+        deposit_litter(
+            &mut world,
+            &killed,
+            killed.len() as f32 * config.litter_nutrients_per_mass,
+        );
+    }
+}
+
 /// System to age plants
 pub fn aging_system(mut plants: Query<&mut PlantBiology>, time: Res<Time>) {
     for mut biology in plants.iter_mut() {
@@ -1,9 +1,10 @@
 use bevy::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use crate::config::*;
 
 /// Individual gene that controls a plant trait
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Gene {
     pub value: f32, // Normalized value 0.0 to 1.0
 }
@@ -20,16 +21,16 @@ impl Gene {
     }
 
     /// Mutate the gene by a small random amount
-    pub fn mutate(&mut self, rng: &mut impl Rng) {
-        if rng.random::<f32>() < MUTATION_RATE {
-            let change = (rng.random::<f32>() - 0.5) * MUTATION_STRENGTH * 2.0;
+    pub fn mutate(&mut self, rng: &mut impl Rng, config: &SimConfig) {
+        if rng.random::<f32>() < config.mutation_rate {
+            let change = (rng.random::<f32>() - 0.5) * config.mutation_strength * 2.0;
             self.value = (self.value + change).clamp(0.0, 1.0);
         }
     }
 }
 
 /// Complete genome for a plant
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Genome {
     pub growth_rate: Gene,              // How fast the plant grows
     pub max_height: Gene,               // Maximum height target
@@ -40,6 +41,11 @@ pub struct Genome {
     pub reproduction_threshold: Gene,   // Energy needed to reproduce
     pub mutation_rate: Gene,            // How much offspring mutate
     pub horizontal_growth_tendency: Gene, // Preference for horizontal vs vertical growth
+    pub cold_tolerance: Gene,           // Resistance to frost and cold
+    pub storage_allocation: Gene,       // Share of surplus energy banked in storage organs
+    pub wood_density: Gene,             // Stem strength, at a higher upkeep
+    pub leaf_drop: Gene,                // When leaves are shed in autumn (highest = evergreen)
+    pub leaf_out: Gene,                 // When leaves regrow in spring
 }
 
 impl Genome {
@@ -55,30 +61,78 @@ impl Genome {
             reproduction_threshold: Gene::random(rng),
             mutation_rate: Gene::random(rng),
             horizontal_growth_tendency: Gene::random(rng),
+            cold_tolerance: Gene::random(rng),
+            storage_allocation: Gene::random(rng),
+            wood_density: Gene::random(rng),
+            leaf_drop: Gene::random(rng),
+            leaf_out: Gene::random(rng),
         }
     }
 
     /// Create offspring genome with mutations
-    pub fn reproduce(&self, rng: &mut impl Rng) -> Self {
+    pub fn reproduce(&self, rng: &mut impl Rng, config: &SimConfig) -> Self {
         let mut child = self.clone();
 
-        child.growth_rate.mutate(rng);
-        child.max_height.mutate(rng);
-        child.leaf_density.mutate(rng);
-        child.root_depth.mutate(rng);
-        child.branching_frequency.mutate(rng);
-        child.photosynthesis_efficiency.mutate(rng);
-        child.reproduction_threshold.mutate(rng);
-        child.horizontal_growth_tendency.mutate(rng);
+        child.growth_rate.mutate(rng, config);
+        child.max_height.mutate(rng, config);
+        child.leaf_density.mutate(rng, config);
+        child.root_depth.mutate(rng, config);
+        child.branching_frequency.mutate(rng, config);
+        child.photosynthesis_efficiency.mutate(rng, config);
+        child.reproduction_threshold.mutate(rng, config);
+        child.horizontal_growth_tendency.mutate(rng, config);
+        child.cold_tolerance.mutate(rng, config);
+        child.storage_allocation.mutate(rng, config);
+        child.wood_density.mutate(rng, config);
+        child.leaf_drop.mutate(rng, config);
+        child.leaf_out.mutate(rng, config);
 
         // Mutation rate itself can mutate, but less frequently
-        if rng.random::<f32>() < MUTATION_RATE * 0.5 {
-            child.mutation_rate.mutate(rng);
+        if rng.random::<f32>() < config.mutation_rate * 0.5 {
+            child.mutation_rate.mutate(rng, config);
         }
 
         child
     }
 
+    /// Names of the genes, in the order of `gene_values`
+    pub const GENE_NAMES: [&'static str; 14] = [
+        "Growth Rate",
+        "Max Height",
+        "Leaf Density",
+        "Root Depth",
+        "Branching",
+        "Photosynthesis",
+        "Reproduction Threshold",
+        "Mutation Rate",
+        "Horizontal Growth",
+        "Cold Tolerance",
+        "Storage Allocation",
+        "Wood Density",
+        "Leaf Drop",
+        "Leaf Out",
+    ];
+
+    /// Normalized value of every gene, in the order of `GENE_NAMES`
+    pub fn gene_values(&self) -> [f32; 14] {
+        [
+            self.growth_rate.value,
+            self.max_height.value,
+            self.leaf_density.value,
+            self.root_depth.value,
+            self.branching_frequency.value,
+            self.photosynthesis_efficiency.value,
+            self.reproduction_threshold.value,
+            self.mutation_rate.value,
+            self.horizontal_growth_tendency.value,
+            self.cold_tolerance.value,
+            self.storage_allocation.value,
+            self.wood_density.value,
+            self.leaf_drop.value,
+            self.leaf_out.value,
+        ]
+    }
+
     /// Calculate genetic distance from another genome (for species tracking)
     pub fn distance(&self, other: &Genome) -> f32 {
         let diff_sum = (self.growth_rate.value - other.growth_rate.value).abs()
@@ -89,9 +143,14 @@ impl Genome {
             + (self.photosynthesis_efficiency.value - other.photosynthesis_efficiency.value).abs()
             + (self.reproduction_threshold.value - other.reproduction_threshold.value).abs()
             + (self.mutation_rate.value - other.mutation_rate.value).abs()
-            + (self.horizontal_growth_tendency.value - other.horizontal_growth_tendency.value).abs();
+            + (self.horizontal_growth_tendency.value - other.horizontal_growth_tendency.value).abs()
+            + (self.cold_tolerance.value - other.cold_tolerance.value).abs()
+            + (self.storage_allocation.value - other.storage_allocation.value).abs()
+            + (self.wood_density.value - other.wood_density.value).abs()
+            + (self.leaf_drop.value - other.leaf_drop.value).abs()
+            + (self.leaf_out.value - other.leaf_out.value).abs();
 
-        diff_sum / 9.0 // Average difference
+        diff_sum / 14.0 // Average difference
     }
 
     /// Get actual values from normalized genes
@@ -139,6 +198,31 @@ impl Genome {
         // 0.0 to 1.0 (0 = vertical only, 1 = horizontal only, 0.5 = balanced)
         self.horizontal_growth_tendency.value
     }
+
+    pub fn get_cold_tolerance(&self) -> f32 {
+        // 0 to 15 °C lower frost and photosynthesis limits
+        self.cold_tolerance.value * 15.0
+    }
+
+    pub fn get_storage_allocation(&self) -> f32 {
+        // 0.0 to 0.5 of surplus energy stored per second
+        self.storage_allocation.value * 0.5
+    }
+
+    pub fn get_wood_strength(&self) -> f32 {
+        // 0.5x to 2.0x the base stem strength
+        0.5 + self.wood_density.value * 1.5
+    }
+
+    pub fn get_leaf_drop_time(&self) -> f32 {
+        // Time of year from the start of autumn (0.5) to the end of winter (1.0, never shed)
+        0.5 + self.leaf_drop.value * 0.5
+    }
+
+    pub fn get_leaf_out_time(&self) -> f32 {
+        // Time of year from the start (0.0) to the end (0.25) of spring
+        self.leaf_out.value * 0.25
+    }
 }
 
 /// Component to track genetic lineage
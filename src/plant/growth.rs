@@ -2,20 +2,28 @@ use bevy::prelude::*;
 use rand::Rng;
 use rand::prelude::{SliceRandom, IndexedRandom};
 use crate::config::*;
-use crate::world::{VoxelWorld, VoxelPos, VoxelType};
-use super::biology::{PlantBiology, PlantStructure, GrowthTimer};
+use crate::world::{Organ, VoxelWorld, VoxelPos, VoxelType};
+use crate::rng::SimulationRng;
+use super::biology::{PlantBiology, GrowthTimer};
 use super::genetics::{Genome, GeneticLineage};
+use super::structure::PlantStructure;
+
+/// Highest branch order that can still fork new branches
+const MAX_BRANCH_ORDER: u32 = 3;
 
 /// System to handle plant growth
 pub fn plant_growth_system(
     mut plants: Query<(Entity, &mut PlantBiology, &mut PlantStructure, &Genome, &GeneticLineage, &mut GrowthTimer)>,
     mut world: ResMut<VoxelWorld>,
+    mut sim_rng: ResMut<SimulationRng>,
+    config: Res<SimConfig>,
     time: Res<Time>,
 ) {
-    let mut rng = rand::rng();
+    let rng = &mut **sim_rng;
 
     for (entity, mut biology, mut structure, genome, lineage, mut growth_timer) in plants.iter_mut() {
-        if !biology.is_alive {
+        // Dormant plants wait for spring
+        if !biology.is_alive || biology.dormant {
             continue;
         }
 
@@ -27,8 +35,7 @@ pub fn plant_growth_system(
 
         // Check if plant has reached max height
         let current_height = structure
-            .voxel_positions
-            .iter()
+            .positions()
             .map(|p| p.y)
             .max()
             .unwrap_or(0);
@@ -37,7 +44,7 @@ pub fn plant_growth_system(
         // (actual cost will be calculated in grow_voxel based on exact position)
         let height_above_root = (current_height - structure.root_position.y).max(0) as f32;
         let estimated_height_multiplier = 1.0 + (height_above_root * 0.1);
-        let estimated_growth_cost = BASE_GROWTH_COST * estimated_height_multiplier;
+        let estimated_growth_cost = config.base_growth_cost * estimated_height_multiplier;
 
         // Check if plant has enough energy to grow at current height
         if biology.energy < estimated_growth_cost {
@@ -55,35 +62,80 @@ pub fn plant_growth_system(
 
         let species_id = lineage.species_id;
 
-        if should_grow_horizontal || should_branch {
-            // Try to grow horizontally from an existing voxel
-            if let Some(&growth_pos) = structure.voxel_positions.choose(&mut rng) {
+        // The seed germinates into the plant's first root when it first grows
+        let root = structure.root_position;
+        if structure.segment(&root).map(|segment| segment.organ) == Some(Organ::Seed) {
+            structure.set_organ(&root, Organ::Root);
+            world.set_voxel_type(&root, VoxelType::PlantMaterial {
+                plant_id: entity.index(),
+                species_id,
+                organ: Organ::Root,
+            });
+        }
+
+        // Shoots grow from the tips of their branches, or from the base before the plant has a stem
+        let stem_tips = structure.tips(Organ::Stem);
+
+        if stem_tips.is_empty() || !(should_grow_horizontal || should_branch) {
+            // Grow upward from the highest tip with room above it
+            let candidates = if stem_tips.is_empty() { vec![root] } else { stem_tips };
+            let highest_tip = candidates
+                .into_iter()
+                .filter(|tip| can_grow_at(VoxelPos::new(tip.x, tip.y + 1, tip.z), &world))
+                .max_by_key(|p| p.y);
+            if let Some(highest_tip) = highest_tip {
+                try_grow_upward(
+                    entity,
+                    &mut biology,
+                    &mut structure,
+                    genome,
+                    highest_tip,
+                    &mut world,
+                    &config,
+                    rng,
+                    species_id,
+                );
+            }
+        } else if should_branch {
+            // Fork a new branch from a bud along a stem
+            let buds: Vec<VoxelPos> = structure
+                .segments()
+                .iter()
+                .filter(|segment| segment.organ == Organ::Stem && segment.order < MAX_BRANCH_ORDER)
+                .map(|segment| segment.pos)
+                .collect();
+            if let Some(&bud) = buds.choose(rng) {
                 try_grow_horizontal(
                     entity,
                     &mut biology,
                     &mut structure,
                     genome,
-                    growth_pos,
+                    bud,
                     &mut world,
-                    &mut rng,
+                    &config,
+                    rng,
                     species_id,
                 );
             }
         } else {
-            // Grow upward from the tallest point
-            if let Some(&highest_pos) = structure
-                .voxel_positions
-                .iter()
-                .max_by_key(|p| p.y)
-            {
-                try_grow_upward(
+            // Extend a branch tip sideways; higher order branches stay shorter
+            let max_height = genome.get_max_height() as usize;
+            let tips: Vec<VoxelPos> = structure
+                .branches()
+                .into_iter()
+                .filter(|branch| branch.organ == Organ::Stem && branch.length < max_height / (branch.order as usize + 1))
+                .map(|branch| branch.tip)
+                .collect();
+            if let Some(&tip) = tips.choose(rng) {
+                try_grow_horizontal(
                     entity,
                     &mut biology,
                     &mut structure,
                     genome,
-                    highest_pos,
+                    tip,
                     &mut world,
-                    &mut rng,
+                    &config,
+                    rng,
                     species_id,
                 );
             }
@@ -98,7 +150,21 @@ pub fn plant_growth_system(
                 &mut structure,
                 genome,
                 &mut world,
-                &mut rng,
+                &config,
+                rng,
+                species_id,
+            );
+        }
+
+        // Plants that bank energy grow storage tubers beside their roots
+        if rng.random::<f32>() < genome.storage_allocation.value * 0.1 {
+            try_grow_storage(
+                entity,
+                &mut biology,
+                &mut structure,
+                &mut world,
+                &config,
+                rng,
                 species_id,
             );
         }
@@ -113,17 +179,18 @@ fn try_grow_upward(
     genome: &Genome,
     from_pos: VoxelPos,
     world: &mut VoxelWorld,
+    config: &SimConfig,
     rng: &mut impl Rng,
     species_id: u32,
 ) {
     let new_pos = VoxelPos::new(from_pos.x, from_pos.y + 1, from_pos.z);
 
     if can_grow_at(new_pos, world) {
-        grow_voxel(plant_id, new_pos, biology, structure, world, species_id);
+        grow_voxel(plant_id, new_pos, Organ::Stem, from_pos, biology, structure, world, config, species_id);
 
         // Maybe add a leaf
         if rng.random::<f32>() < genome.get_leaf_density() {
-            add_leaf(plant_id, new_pos, biology, structure, world, rng, species_id);
+            add_leaf(plant_id, new_pos, biology, structure, world, config, rng, species_id);
         }
     }
 }
@@ -136,24 +203,29 @@ fn try_grow_horizontal(
     genome: &Genome,
     from_pos: VoxelPos,
     world: &mut VoxelWorld,
+    config: &SimConfig,
     rng: &mut impl Rng,
     species_id: u32,
 ) {
-    // Try to grow in a random horizontal direction
+    // Grow toward light: open directions are picked in proportion to their light level
     let directions = [
         VoxelPos::new(from_pos.x + 1, from_pos.y, from_pos.z),
         VoxelPos::new(from_pos.x - 1, from_pos.y, from_pos.z),
         VoxelPos::new(from_pos.x, from_pos.y, from_pos.z + 1),
         VoxelPos::new(from_pos.x, from_pos.y, from_pos.z - 1),
     ];
+    let light_at = |pos: &VoxelPos| match world.get(pos) {
+        Some(voxel) if voxel.voxel_type.is_air() => voxel.environment.light_level,
+        _ => 0.0,
+    };
 
-    if let Some(&new_pos) = directions.choose(rng) {
+    if let Ok(&new_pos) = directions.choose_weighted(rng, light_at) {
         if can_grow_at(new_pos, world) {
-            grow_voxel(plant_id, new_pos, biology, structure, world, species_id);
+            grow_voxel(plant_id, new_pos, Organ::Stem, from_pos, biology, structure, world, config, species_id);
 
             // Higher chance of leaf on branches
             if rng.random::<f32>() < genome.get_leaf_density() * 1.5 {
-                add_leaf(plant_id, new_pos, biology, structure, world, rng, species_id);
+                add_leaf(plant_id, new_pos, biology, structure, world, config, rng, species_id);
             }
         }
     }
@@ -166,15 +238,15 @@ fn try_grow_root(
     structure: &mut PlantStructure,
     genome: &Genome,
     world: &mut VoxelWorld,
+    config: &SimConfig,
     rng: &mut impl Rng,
     species_id: u32,
 ) {
-    // Find deepest root
+    // Extend the deepest root tip
     let deepest_root = structure
-        .root_positions
-        .iter()
+        .tips(Organ::Root)
+        .into_iter()
         .min_by_key(|p| p.y)
-        .copied()
         .unwrap_or(structure.root_position);
 
     // Check if we can grow deeper
@@ -186,26 +258,54 @@ fn try_grow_root(
     let new_pos = VoxelPos::new(deepest_root.x, deepest_root.y - 1, deepest_root.z);
 
     if can_grow_root_at(new_pos, world) {
-        grow_voxel(plant_id, new_pos, biology, structure, world, species_id);
-        structure.root_positions.push(new_pos);
+        grow_voxel(plant_id, new_pos, Organ::Root, deepest_root, biology, structure, world, config, species_id);
     }
 }
 
-/// Add a leaf voxel
+/// Try to grow a storage tuber in the soil beside or below a root
+fn try_grow_storage(
+    plant_id: Entity,
+    biology: &mut PlantBiology,
+    structure: &mut PlantStructure,
+    world: &mut VoxelWorld,
+    config: &SimConfig,
+    rng: &mut impl Rng,
+    species_id: u32,
+) {
+    let roots: Vec<VoxelPos> = structure.organ_positions(Organ::Root).collect();
+    let Some(&root) = roots.choose(rng) else {
+        return;
+    };
+    let sides = [
+        VoxelPos::new(root.x + 1, root.y, root.z),
+        VoxelPos::new(root.x - 1, root.y, root.z),
+        VoxelPos::new(root.x, root.y, root.z + 1),
+        VoxelPos::new(root.x, root.y, root.z - 1),
+        VoxelPos::new(root.x, root.y - 1, root.z),
+    ];
+
+    if let Some(&pos) = sides.choose(rng) {
+        if matches!(world.get(&pos).map(|v| v.voxel_type), Some(VoxelType::Soil)) {
+            grow_voxel(plant_id, pos, Organ::Storage, root, biology, structure, world, config, species_id);
+        }
+    }
+}
+
+/// Add a leaf voxel beside a stem
 fn add_leaf(
     plant_id: Entity,
     pos: VoxelPos,
     biology: &mut PlantBiology,
     structure: &mut PlantStructure,
     world: &mut VoxelWorld,
+    config: &SimConfig,
     rng: &mut impl Rng,
     species_id: u32,
 ) {
-    // Try to place leaf adjacent to the position
+    // Try to place leaf beside the position; leaves above would cap the stem tip
     let offsets = [
         VoxelPos::new(1, 0, 0),
         VoxelPos::new(-1, 0, 0),
-        VoxelPos::new(0, 1, 0),
         VoxelPos::new(0, 0, 1),
         VoxelPos::new(0, 0, -1),
     ];
@@ -214,8 +314,7 @@ fn add_leaf(
         let leaf_pos = VoxelPos::new(pos.x + offset.x, pos.y + offset.y, pos.z + offset.z);
 
         if can_grow_at(leaf_pos, world) {
-            grow_voxel(plant_id, leaf_pos, biology, structure, world, species_id);
-            structure.leaf_positions.push(leaf_pos);
+            grow_voxel(plant_id, leaf_pos, Organ::Leaf, pos, biology, structure, world, config, species_id);
         }
     }
 }
@@ -238,36 +337,43 @@ fn can_grow_root_at(pos: VoxelPos, world: &VoxelWorld) -> bool {
     }
 }
 
-/// Actually grow a voxel
-fn grow_voxel(
+/// Energy needed to grow a voxel at a position
+pub(super) fn growth_cost(pos: &VoxelPos, structure: &PlantStructure, config: &SimConfig) -> f32 {
+    // The higher above the root, the more energy it costs to grow
+    let height_above_root = (pos.y - structure.root_position.y).max(0) as f32;
+
+    // Exponential scaling: cost = base_growth_cost * (1 + 0.1 * height)
+    // This makes tall growth progressively harder
+    let height_multiplier = 1.0 + (height_above_root * 0.1);
+    config.base_growth_cost * height_multiplier
+}
+
+/// Actually grow a voxel, attached to the segment at `parent`
+pub(super) fn grow_voxel(
     plant_id: Entity,
     pos: VoxelPos,
+    organ: Organ,
+    parent: VoxelPos,
     biology: &mut PlantBiology,
     structure: &mut PlantStructure,
     world: &mut VoxelWorld,
+    config: &SimConfig,
     species_id: u32,
 ) {
-    // Calculate height-based growth cost
-    // The higher above the root, the more energy it costs to grow
-    let height_above_root = (pos.y - structure.root_position.y).max(0) as f32;
-
-    // Exponential scaling: cost = BASE_GROWTH_COST * (1 + 0.1 * height)
-    // This makes tall growth progressively harder
-    let height_multiplier = 1.0 + (height_above_root * 0.1);
-    let total_growth_cost = BASE_GROWTH_COST * height_multiplier;
-
-    // Deduct energy cost
-    biology.energy -= total_growth_cost;
+    // Add to structure, unless the voxel is already part of the plant or its parent is not
+    let cost = growth_cost(&pos, structure, config);
+    if !structure.add_segment(pos, organ, parent) {
+        return;
+    }
 
-    // Add to structure
-    structure.voxel_positions.push(pos);
-    biology.total_mass = structure.voxel_positions.len() as u32;
+    // Deduct height-based growth cost
+    biology.energy -= cost;
+    biology.total_mass = structure.len() as u32;
 
     // Update world
-    if let Some(voxel) = world.get_mut(&pos) {
-        voxel.voxel_type = VoxelType::PlantMaterial {
-            plant_id: plant_id.index(),
-            species_id,
-        };
-    }
+    world.set_voxel_type(&pos, VoxelType::PlantMaterial {
+        plant_id: plant_id.index(),
+        species_id,
+        organ,
+    });
 }
@@ -1,10 +1,19 @@
 pub mod genetics;
 pub mod biology;
+pub mod structure;
 pub mod growth;
 pub mod reproduction;
+pub mod transport;
+pub mod support;
+pub mod phenology;
 
 pub use genetics::{Gene, Genome, GeneticLineage};
-pub use biology::{PlantBiology, PlantStructure, GrowthTimer, photosynthesis_system,
-                 resource_absorption_system, maintenance_cost_system, aging_system};
+pub use biology::{PlantBiology, GrowthTimer, photosynthesis_system,
+                 resource_absorption_system, maintenance_cost_system, storage_system, frost_damage_system, aging_system,
+                 temperature_photosynthesis_factor, temperature_maintenance_factor, organ_maintenance_factor};
+pub use structure::{PlantStructure, Segment, BranchMetrics};
 pub use growth::plant_growth_system;
+pub use transport::{TransportNetwork, exchange_reserves};
+pub use support::{SegmentLoad, segment_loads, structural_support_system};
+pub use phenology::phenology_system;
 pub use reproduction::{reproduction_system, spawn_plant, cleanup_dead_plants_system, SpeciesCounter};
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use crate::config::*;
+use crate::world::{Organ, VoxelWorld, YearCycle, deposit_litter};
+use super::biology::PlantBiology;
+use super::genetics::{Genome, GeneticLineage};
+use super::growth::{grow_voxel, growth_cost};
+use super::structure::PlantStructure;
+
+/// System to shed leaves in autumn and regrow them in spring
+/// Deciduous plants stop paying leaf upkeep through winter but must buy their leaves back each spring;
+/// plants whose leaf drop time is the end of the year never shed and stay evergreen
+pub fn phenology_system(
+    mut plants: Query<(Entity, &mut PlantBiology, &mut PlantStructure, &Genome, &GeneticLineage)>,
+    mut world: ResMut<VoxelWorld>,
+    year_cycle: Res<YearCycle>,
+    config: Res<SimConfig>,
+) {
+    let time_of_year = year_cycle.time_of_year;
+
+    for (entity, mut biology, mut structure, genome, lineage) in plants.iter_mut() {
+        if !biology.is_alive {
+            continue;
+        }
+
+        if !biology.dormant && time_of_year >= genome.get_leaf_drop_time() {
+            // Shed leaves fall as litter, leaving buds on the stems
+            biology.dormant = true;
+            let shed = structure.shed_leaves();
+            biology.total_mass = structure.len() as u32;
+            deposit_litter(
+                &mut world,
+                &shed,
+                shed.len() as f32 * config.litter_nutrients_per_mass,
+            );
+        } else if biology.dormant && time_of_year >= genome.get_leaf_out_time() && time_of_year < 0.5 {
+            biology.dormant = false;
+        }
+
+        if biology.dormant || structure.leaf_buds.is_empty() {
+            continue;
+        }
+
+        // Leaf out as energy allows; buds on lost stems or with something else in the way are gone
+        for (leaf, stem) in std::mem::take(&mut structure.leaf_buds) {
+            let open = world.get(&leaf).is_some_and(|voxel| voxel.voxel_type.is_air());
+            if !open || structure.segment(&stem).is_none() {
+                continue;
+            }
+
+            if biology.energy < growth_cost(&leaf, &structure, &config) {
+                structure.leaf_buds.push((leaf, stem));
+                continue;
+            }
+            grow_voxel(
+                entity,
+                leaf,
+                Organ::Leaf,
+                stem,
+                &mut biology,
+                &mut structure,
+                &mut world,
+                &config,
+                lineage.species_id,
+            );
+        }
+    }
+}
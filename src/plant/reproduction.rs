@@ -1,9 +1,12 @@
 use bevy::prelude::*;
 use rand::Rng;
 use crate::config::*;
-use crate::world::{VoxelWorld, VoxelPos, VoxelType};
-use super::biology::{PlantBiology, PlantStructure, GrowthTimer};
+use crate::world::{Organ, VoxelWorld, VoxelPos, VoxelType, deposit_litter};
+use crate::rng::SimulationRng;
+use super::biology::{PlantBiology, GrowthTimer};
 use super::genetics::{Genome, GeneticLineage};
+use super::structure::PlantStructure;
+use super::growth::grow_voxel;
 
 /// Tracks the next species ID to assign
 #[derive(Resource, Default)]
@@ -17,14 +20,16 @@ const SPECIES_DIVERGENCE_THRESHOLD: f32 = 0.25;
 /// System to handle plant reproduction
 pub fn reproduction_system(
     mut commands: Commands,
-    mut plants: Query<(Entity, &mut PlantBiology, &PlantStructure, &Genome, &GeneticLineage)>,
-    world: Res<VoxelWorld>,
+    mut plants: Query<(Entity, &mut PlantBiology, &mut PlantStructure, &Genome, &GeneticLineage)>,
+    mut world: ResMut<VoxelWorld>,
     mut species_counter: ResMut<SpeciesCounter>,
+    mut sim_rng: ResMut<SimulationRng>,
+    config: Res<SimConfig>,
 ) {
-    let mut rng = rand::rng();
+    let rng = &mut **sim_rng;
     let mut seeds_to_spawn = Vec::new();
 
-    for (entity, mut biology, structure, genome, lineage) in plants.iter_mut() {
+    for (entity, mut biology, mut structure, genome, lineage) in plants.iter_mut() {
         if !biology.is_alive {
             continue;
         }
@@ -33,13 +38,20 @@ pub fn reproduction_system(
 
         // Check if plant has enough energy to reproduce
         if biology.energy >= reproduction_threshold {
+            // Seeds only come from flowers, so a plant without one spends this tick blooming
+            // and sets seed from the flower on a later tick
+            let Some(flower) = structure.organ_positions(Organ::Flower).next() else {
+                grow_flower(entity, &mut biology, &mut structure, &mut world, &config, lineage.species_id);
+                continue;
+            };
+
             // Deduct reproduction cost
-            biology.energy -= REPRODUCTION_ENERGY_COST;
+            biology.energy -= config.reproduction_energy_cost;
 
             // Find a position for the seed
-            if let Some(seed_pos) = find_seed_position(&structure.root_position, &world, &mut rng) {
+            if let Some(seed_pos) = find_seed_position(&structure.root_position, &world, rng, config.seed_dispersal_range) {
                 // Create offspring genome
-                let offspring_genome = genome.reproduce(&mut rng);
+                let offspring_genome = genome.reproduce(rng, &config);
 
                 // Calculate genetic distance to determine species
                 let genetic_distance = genome.distance(&offspring_genome);
@@ -63,6 +75,14 @@ pub fn reproduction_system(
                     offspring_species_id,
                 ));
 
+                // The flower ripens into a seed head, so the plant must bloom again to reproduce again
+                structure.set_organ(&flower, Organ::Seed);
+                world.set_voxel_type(&flower, VoxelType::PlantMaterial {
+                    plant_id: entity.index(),
+                    species_id: lineage.species_id,
+                    organ: Organ::Seed,
+                });
+
                 println!(
                     "Plant reproduced! Generation {} -> {}",
                     lineage.generation,
@@ -72,9 +92,39 @@ pub fn reproduction_system(
         }
     }
 
-    // Spawn seeds
+    // Spawn seeds, unless another seed landed on the same spot this tick
     for (pos, genome, generation, parent_id, species_id) in seeds_to_spawn {
-        spawn_plant(&mut commands, pos, genome, generation, parent_id, species_id);
+        if is_valid_seed_position(&pos, &world) {
+            spawn_plant(&mut commands, &mut world, pos, genome, generation, parent_id, species_id);
+        }
+    }
+}
+
+/// Grow a flower beside the plant's highest stem voxel with room, like a bud in a leaf axil
+/// Flowers never sit on top of a stem tip, so the plant can keep growing upward after flowering;
+/// a seedling without a stem cannot flower yet
+fn grow_flower(
+    plant_id: Entity,
+    biology: &mut PlantBiology,
+    structure: &mut PlantStructure,
+    world: &mut VoxelWorld,
+    config: &SimConfig,
+    species_id: u32,
+) {
+    let mut stems: Vec<VoxelPos> = structure.organ_positions(Organ::Stem).collect();
+    stems.sort_by_key(|pos| std::cmp::Reverse(pos.y));
+
+    for stem in stems {
+        let sides = [
+            VoxelPos::new(stem.x + 1, stem.y, stem.z),
+            VoxelPos::new(stem.x - 1, stem.y, stem.z),
+            VoxelPos::new(stem.x, stem.y, stem.z + 1),
+            VoxelPos::new(stem.x, stem.y, stem.z - 1),
+        ];
+        if let Some(&pos) = sides.iter().find(|pos| world.get(pos).is_some_and(|voxel| voxel.voxel_type.is_air())) {
+            grow_voxel(plant_id, pos, Organ::Flower, stem, biology, structure, world, config, species_id);
+            return;
+        }
     }
 }
 
@@ -83,17 +133,20 @@ fn find_seed_position(
     parent_pos: &VoxelPos,
     world: &VoxelWorld,
     rng: &mut impl Rng,
+    dispersal_range: i32,
 ) -> Option<VoxelPos> {
     // Try random positions within dispersal range
     for _ in 0..20 {
-        let offset_x = rng.random_range(-SEED_DISPERSAL_RANGE..=SEED_DISPERSAL_RANGE);
-        let offset_z = rng.random_range(-SEED_DISPERSAL_RANGE..=SEED_DISPERSAL_RANGE);
+        let offset_x = rng.random_range(-dispersal_range..=dispersal_range);
+        let offset_z = rng.random_range(-dispersal_range..=dispersal_range);
 
-        let candidate = VoxelPos::new(
-            parent_pos.x + offset_x,
-            parent_pos.y,
-            parent_pos.z + offset_z,
-        );
+        // Seeds land on the terrain surface, which may be higher or lower than the parent
+        let x = parent_pos.x + offset_x;
+        let z = parent_pos.z + offset_z;
+        let Some(y) = world.surface_height(x, z) else {
+            continue;
+        };
+        let candidate = VoxelPos::new(x, y, z);
 
         // Check if position is valid (on soil and not occupied)
         if is_valid_seed_position(&candidate, world) {
@@ -104,7 +157,7 @@ fn find_seed_position(
     None
 }
 
-/// Check if a position is valid for planting a seed
+/// Check if a position is valid for planting a seed (a surface soil voxel)
 fn is_valid_seed_position(pos: &VoxelPos, world: &VoxelWorld) -> bool {
     // Check if the position is soil
     if let Some(voxel) = world.get(pos) {
@@ -122,26 +175,35 @@ fn is_valid_seed_position(pos: &VoxelPos, world: &VoxelWorld) -> bool {
     false
 }
 
-/// Spawn a new plant
+/// Spawn a new plant, turning the soil voxel at `root_pos` into its seed
 pub fn spawn_plant(
     commands: &mut Commands,
+    world: &mut VoxelWorld,
     root_pos: VoxelPos,
     genome: Genome,
     generation: u32,
     parent_id: Option<Entity>,
     species_id: u32,
 ) {
-    commands.spawn((
-        PlantBiology::default(),
-        PlantStructure::new(root_pos),
-        genome,
-        GeneticLineage {
-            generation,
-            parent_id,
-            species_id,
-        },
-        GrowthTimer::default(),
-    ));
+    let entity = commands
+        .spawn((
+            PlantBiology::default(),
+            PlantStructure::new(root_pos),
+            genome,
+            GeneticLineage {
+                generation,
+                parent_id,
+                species_id,
+            },
+            GrowthTimer::default(),
+        ))
+        .id();
+
+    world.set_voxel_type(&root_pos, VoxelType::PlantMaterial {
+        plant_id: entity.index(),
+        species_id,
+        organ: Organ::Seed,
+    });
 }
 
 /// System to remove dead plants from the world
@@ -149,16 +211,14 @@ pub fn cleanup_dead_plants_system(
     mut commands: Commands,
     dead_plants: Query<(Entity, &PlantBiology, &PlantStructure), Changed<PlantBiology>>,
     mut world: ResMut<VoxelWorld>,
+    config: Res<SimConfig>,
 ) {
     for (entity, biology, structure) in dead_plants.iter() {
         if !biology.is_alive {
-            // Remove plant voxels from world
-            for pos in &structure.voxel_positions {
-                if let Some(voxel) = world.get_mut(pos) {
-                    // Convert to soil (decomposition)
-                    voxel.voxel_type = VoxelType::Soil;
-                }
-            }
+            // Leave the plant's voxels as litter that returns its mass to the soil
+            let nutrients = biology.total_mass as f32 * config.litter_nutrients_per_mass;
+            let positions: Vec<VoxelPos> = structure.positions().collect();
+            deposit_litter(&mut world, &positions, nutrients);
 
             // Despawn entity
             commands.entity(entity).despawn();
@@ -0,0 +1,257 @@
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::world::{Organ, VoxelPos};
+
+/// One voxel of a plant, linked to the segment it grew from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub pos: VoxelPos,
+    pub organ: Organ,
+    pub parent: Option<VoxelPos>, // Segment this one grew from; `None` for the base
+    pub branch: u32, // ID of the branch the segment belongs to
+    pub order: u32, // Branch order: 0 for the taproot and main stem, one more for each fork
+}
+
+/// Measurements of one branch of a plant
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchMetrics {
+    pub id: u32,
+    pub organ: Organ, // Stem for shoots, Root for roots
+    pub order: u32,
+    pub length: usize, // Stem or root segments along the branch
+    pub leaves: usize, // Leaves attached to the branch
+    pub tip: VoxelPos, // Growing end of the branch (apical meristem)
+}
+
+/// Component to track plant structure in the world
+/// Segments form a tree rooted at the base: stems and roots grow in branches from their tips,
+/// and leaves, flowers and storage organs hang off the branch they grew from
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct PlantStructure {
+    pub root_position: VoxelPos,
+    segments: Vec<Segment>, // In growth order, so every segment comes after its parent
+    index: HashMap<VoxelPos, usize>, // Position of each segment in `segments`
+    branch_tips: HashMap<u32, VoxelPos>, // Most recent stem or root segment of each branch
+    next_branch: u32,
+    pub leaf_buds: Vec<(VoxelPos, VoxelPos)>, // Where shed leaves grew, and the segment each grew from
+}
+
+impl PlantStructure {
+    pub fn new(root: VoxelPos) -> Self {
+        Self {
+            root_position: root,
+            segments: vec![Segment {
+                pos: root,
+                organ: Organ::Seed,
+                parent: None,
+                branch: 0,
+                order: 0,
+            }],
+            index: HashMap::from([(root, 0)]),
+            branch_tips: HashMap::new(),
+            next_branch: 1,
+            leaf_buds: Vec::new(),
+        }
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    pub fn segment(&self, pos: &VoxelPos) -> Option<&Segment> {
+        self.index.get(pos).map(|&i| &self.segments[i])
+    }
+
+    /// Number of voxels in the plant
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Positions of all of the plant's voxels
+    pub fn positions(&self) -> impl Iterator<Item = VoxelPos> + '_ {
+        self.segments.iter().map(|segment| segment.pos)
+    }
+
+    /// Positions of the plant's voxels of one organ
+    pub fn organ_positions(&self, organ: Organ) -> impl Iterator<Item = VoxelPos> + '_ {
+        self.segments
+            .iter()
+            .filter(move |segment| segment.organ == organ)
+            .map(|segment| segment.pos)
+    }
+
+    /// Add a segment grown from `parent`, returning false if `parent` is not part of the plant
+    /// or `pos` already is
+    /// A stem or root continues its parent's branch if it is the first to grow on from the parent's tip,
+    /// and otherwise forks a new branch one order higher; other organs join their parent's branch
+    pub fn add_segment(&mut self, pos: VoxelPos, organ: Organ, parent: VoxelPos) -> bool {
+        if self.index.contains_key(&pos) {
+            return false;
+        }
+        let Some(parent_segment) = self.segment(&parent) else {
+            return false;
+        };
+        let (parent_branch, parent_order, parent_organ) = (parent_segment.branch, parent_segment.order, parent_segment.organ);
+
+        let (branch, order) = if !is_axis(organ) {
+            (parent_branch, parent_order)
+        } else if parent_organ == organ && self.tip(parent_branch) == Some(parent) {
+            (parent_branch, parent_order)
+        } else {
+            // Shoots start their own axis at the base; anything else is a side branch
+            let order = if parent_organ == organ { parent_order + 1 } else { parent_order };
+            self.next_branch += 1;
+            (self.next_branch - 1, order)
+        };
+
+        self.index.insert(pos, self.segments.len());
+        if is_axis(organ) {
+            self.branch_tips.insert(branch, pos);
+        }
+        self.segments.push(Segment {
+            pos,
+            organ,
+            parent: Some(parent),
+            branch,
+            order,
+        });
+        true
+    }
+
+    /// Change the organ of an existing segment, such as a seed germinating into a root
+    pub fn set_organ(&mut self, pos: &VoxelPos, organ: Organ) {
+        if let Some(&i) = self.index.get(pos) {
+            self.segments[i].organ = organ;
+            self.reindex();
+        }
+    }
+
+    /// Remove a segment and everything that grew from it, returning the removed positions
+    pub fn remove_segment(&mut self, pos: &VoxelPos) -> Vec<VoxelPos> {
+        if self.segment(pos).is_none() {
+            return Vec::new();
+        }
+
+        // Children always come after their parents, so one pass finds the whole subtree
+        let mut removed = HashSet::from([*pos]);
+        for segment in &self.segments {
+            if segment.parent.is_some_and(|parent| removed.contains(&parent)) {
+                removed.insert(segment.pos);
+            }
+        }
+
+        let mut positions = Vec::with_capacity(removed.len());
+        self.segments.retain(|segment| {
+            if removed.contains(&segment.pos) {
+                positions.push(segment.pos);
+                return false;
+            }
+            true
+        });
+        self.reindex();
+        positions
+    }
+
+    /// Remove every leaf, leaving a bud where each was so it can regrow, and return the shed positions
+    /// Buds still waiting to leaf out from an earlier year are kept
+    pub fn shed_leaves(&mut self) -> Vec<VoxelPos> {
+        let mut shed = Vec::new();
+        let mut buds = std::mem::take(&mut self.leaf_buds);
+        let mut budded: HashSet<VoxelPos> = buds.iter().map(|(leaf, _)| *leaf).collect();
+
+        // Leaves never have anything growing from them, so no other segments are lost
+        self.segments.retain(|segment| {
+            if segment.organ != Organ::Leaf {
+                return true;
+            }
+            shed.push(segment.pos);
+            if let Some(parent) = segment.parent
+                && budded.insert(segment.pos)
+            {
+                buds.push((segment.pos, parent));
+            }
+            false
+        });
+
+        self.leaf_buds = buds;
+        self.reindex();
+        shed
+    }
+
+    /// Rebuild the position and branch tip lookups after segments are removed or change organ
+    fn reindex(&mut self) {
+        self.index.clear();
+        self.branch_tips.clear();
+        for (i, segment) in self.segments.iter().enumerate() {
+            self.index.insert(segment.pos, i);
+            if is_axis(segment.organ) {
+                self.branch_tips.insert(segment.branch, segment.pos);
+            }
+        }
+    }
+
+    /// Growing end of a branch: its most recent stem or root segment
+    pub fn tip(&self, branch: u32) -> Option<VoxelPos> {
+        self.branch_tips.get(&branch).copied()
+    }
+
+    /// Tips of all branches of one organ (stem or root)
+    pub fn tips(&self, organ: Organ) -> Vec<VoxelPos> {
+        self.branches()
+            .into_iter()
+            .filter(|branch| branch.organ == organ)
+            .map(|branch| branch.tip)
+            .collect()
+    }
+
+    /// Metrics of every stem and root branch, in the order they were started
+    pub fn branches(&self) -> Vec<BranchMetrics> {
+        let mut branches: Vec<BranchMetrics> = Vec::new();
+        let mut index: HashMap<u32, usize> = HashMap::new();
+
+        for segment in self.segments.iter().filter(|segment| is_axis(segment.organ)) {
+            match index.get(&segment.branch) {
+                Some(&i) => {
+                    branches[i].length += 1;
+                    branches[i].tip = segment.pos;
+                }
+                None => {
+                    index.insert(segment.branch, branches.len());
+                    branches.push(BranchMetrics {
+                        id: segment.branch,
+                        organ: segment.organ,
+                        order: segment.order,
+                        length: 1,
+                        leaves: 0,
+                        tip: segment.pos,
+                    });
+                }
+            }
+        }
+
+        for segment in self.segments.iter().filter(|segment| segment.organ == Organ::Leaf) {
+            if let Some(&i) = index.get(&segment.branch) {
+                branches[i].leaves += 1;
+            }
+        }
+
+        branches.sort_by_key(|branch| branch.id);
+        branches
+    }
+
+    /// Metrics of the branch a segment belongs to
+    pub fn branch_of(&self, pos: &VoxelPos) -> Option<BranchMetrics> {
+        let branch = self.segment(pos)?.branch;
+        self.branches().into_iter().find(|metrics| metrics.id == branch)
+    }
+}
+
+/// Stems and roots make up branches; other organs hang off them
+fn is_axis(organ: Organ) -> bool {
+    matches!(organ, Organ::Stem | Organ::Root)
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+use crate::config::*;
+use crate::world::{Organ, VoxelWorld, VoxelPos, deposit_litter};
+use super::biology::PlantBiology;
+use super::genetics::Genome;
+use super::structure::PlantStructure;
+
+/// Mechanical load on one segment from everything that grew from it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SegmentLoad {
+    pub weight: f32, // Voxels the segment supports
+    pub moment: f32, // Bending moment: supported weight times its horizontal distance from the segment
+}
+
+/// Load on every segment of a plant
+/// Weight hanging out to one side bends a stem; weight balanced on opposite sides cancels out
+pub fn segment_loads(structure: &PlantStructure) -> HashMap<VoxelPos, SegmentLoad> {
+    // Weight of each segment's subtree and its first moments about the world origin
+    let mut totals: HashMap<VoxelPos, (f32, f32, f32)> = HashMap::new();
+
+    // Children come after their parents, so walking backwards adds every child before its parent
+    for segment in structure.segments().iter().rev() {
+        let (weight, moment_x, moment_z) = {
+            let total = totals.entry(segment.pos).or_default();
+            total.0 += 1.0;
+            total.1 += segment.pos.x as f32;
+            total.2 += segment.pos.z as f32;
+            *total
+        };
+        if let Some(parent) = segment.parent {
+            let total = totals.entry(parent).or_default();
+            total.0 += weight;
+            total.1 += moment_x;
+            total.2 += moment_z;
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|(pos, (weight, moment_x, moment_z))| {
+            // Moments about the segment itself; the segment's own voxel adds weight but no leverage
+            let x = moment_x - weight * pos.x as f32;
+            let z = moment_z - weight * pos.z as f32;
+            let load = SegmentLoad {
+                weight: weight - 1.0,
+                moment: (x * x + z * z).sqrt(),
+            };
+            (pos, load)
+        })
+        .collect()
+}
+
+/// System to break overloaded stems
+/// A stem above ground snaps when its bending moment exceeds its strength, or buckles when the weight
+/// it bears exceeds its compression strength, and everything that grew from it falls to the ground as litter
+pub fn structural_support_system(
+    mut plants: Query<(&mut PlantBiology, &mut PlantStructure, &Genome)>,
+    mut world: ResMut<VoxelWorld>,
+    config: Res<SimConfig>,
+) {
+    for (mut biology, mut structure, genome) in plants.iter_mut() {
+        if !biology.is_alive {
+            continue;
+        }
+
+        let strength = config.stem_strength * genome.get_wood_strength();
+        let compression_strength = config.stem_compression_strength * genome.get_wood_strength();
+        let loads = segment_loads(&structure);
+        let overloaded: Vec<VoxelPos> = structure
+            .segments()
+            .iter()
+            .filter(|segment| {
+                segment.organ == Organ::Stem
+                    && segment.pos.y >= world.ground_level(segment.pos.x, segment.pos.z)
+                    && loads.get(&segment.pos).is_some_and(|load| {
+                        load.moment > strength || load.weight > compression_strength
+                    })
+            })
+            .map(|segment| segment.pos)
+            .collect();
+
+        // Breaks nearest the base come first and take any overloaded stems above them along
+        let broken: Vec<VoxelPos> = overloaded
+            .iter()
+            .flat_map(|pos| structure.remove_segment(pos))
+            .collect();
+        if broken.is_empty() {
+            continue;
+        }
+
+        biology.total_mass = structure.len() as u32;
+        deposit_litter(
+            &mut world,
+            &broken,
+            broken.len() as f32 * config.litter_nutrients_per_mass,
+        );
+    }
+}
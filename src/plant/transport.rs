@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use crate::config::*;
+use crate::world::VoxelPos;
+use super::biology::PlantBiology;
+use super::structure::PlantStructure;
+
+/// Routes from each of a plant's voxels back to its base (`root_position`), down the branches they grew on
+/// Follows each segment's parent through the structure's own index, so it costs nothing to set up
+/// and always matches the plant's current shape; voxels that are not part of the plant have no route
+pub struct TransportNetwork<'a> {
+    structure: &'a PlantStructure,
+}
+
+impl<'a> TransportNetwork<'a> {
+    pub fn new(structure: &'a PlantStructure) -> Self {
+        Self { structure }
+    }
+
+    /// Voxels energy passes through on its way from `pos` to the base, starting with `pos` itself
+    /// Empty for the base, `None` if `pos` is not connected to it
+    fn route(&self, pos: &VoxelPos) -> Option<Vec<VoxelPos>> {
+        let mut route = Vec::new();
+        let mut current = *pos;
+        while let Some(next) = self.structure.segment(&current)?.parent {
+            route.push(current);
+            current = next;
+        }
+        Some(route)
+    }
+
+    /// Number of voxels between `pos` and the base, if it is connected
+    pub fn distance(&self, pos: &VoxelPos) -> Option<usize> {
+        self.route(pos).map(|route| route.len())
+    }
+
+    /// Move energy from source voxels to the base, returning how much arrives
+    /// Each voxel passed loses `transport_loss` of the flow, and each voxel, the base included, carries
+    /// at most `transport_capacity` per second in total; energy that does not fit is wasted
+    pub fn deliver(&self, sources: &[(VoxelPos, f32)], config: &SimConfig, delta_secs: f32) -> f32 {
+        let limit = config.transport_capacity * delta_secs;
+        let base = self.structure.root_position;
+        let mut carried: HashMap<VoxelPos, f32> = HashMap::new();
+        let mut delivered = 0.0;
+
+        for (source, amount) in sources {
+            let Some(route) = self.route(source) else {
+                continue;
+            };
+
+            // The busiest voxel on the route, or the base everything flows into, limits the flow
+            let room = route
+                .iter()
+                .chain([&base])
+                .map(|pos| limit - carried.get(pos).copied().unwrap_or(0.0))
+                .fold(f32::INFINITY, f32::min)
+                .max(0.0);
+            let flow = amount.min(room);
+            for pos in route.iter().chain([&base]) {
+                *carried.entry(*pos).or_default() += flow;
+            }
+
+            delivered += flow * (1.0 - config.transport_loss).powi(route.len() as i32);
+        }
+
+        delivered
+    }
+}
+
+/// Move energy between a plant's working energy and its reserves in storage organs
+/// Energy above `storage_fill_energy` is stored in proportion to `allocation`, and reserves are drawn
+/// when energy falls below `storage_draw_energy`, losing energy on the way back from the storage organs
+/// Each connected storage voxel holds `storage_capacity` and moves at most `storage_rate` per second
+pub fn exchange_reserves(
+    biology: &mut PlantBiology,
+    storage_voxels: usize,
+    mean_distance: f32,
+    allocation: f32,
+    config: &SimConfig,
+    delta_secs: f32,
+) {
+    // Reserves held in organs the plant has lost are gone
+    let capacity = storage_voxels as f32 * config.storage_capacity;
+    biology.reserves = biology.reserves.min(capacity);
+    let max_flow = storage_voxels as f32 * config.storage_rate * delta_secs;
+
+    if biology.energy > config.storage_fill_energy {
+        let deposit = ((biology.energy - config.storage_fill_energy) * allocation)
+            .min(max_flow)
+            .min(capacity - biology.reserves);
+        biology.energy -= deposit;
+        biology.reserves += deposit;
+    } else if biology.energy < config.storage_draw_energy {
+        let withdrawal = (config.storage_draw_energy - biology.energy)
+            .min(max_flow)
+            .min(biology.reserves);
+        biology.reserves -= withdrawal;
+        biology.energy += withdrawal * (1.0 - config.transport_loss).powf(mean_distance);
+    }
+}
@@ -1,39 +1,238 @@
 use bevy::prelude::*;
 use crate::config::*;
-use crate::world::{VoxelWorld, VoxelPos};
+use crate::plant::{PlantBiology, GeneticLineage, Genome};
+use crate::world::{ChunkPos, Voxel, VoxelWorld, VoxelPos, VoxelType};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hasher};
 
-/// Component to mark the world mesh
+/// Plugin for drawing the voxel world as one mesh per chunk
+pub struct RenderingPlugin;
+
+impl Plugin for RenderingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RenderState::default())
+            .init_resource::<ChunkMeshes>()
+            .init_resource::<ColorSettings>()
+            .add_systems(Startup, setup_rendering)
+            .add_systems(Update, (color_mode_hotkey_system, update_world_mesh_system).chain());
+    }
+}
+
+/// Component to mark the mesh of a world chunk
 #[derive(Component)]
-pub struct WorldMesh;
+pub struct WorldMesh {
+    pub chunk: ChunkPos,
+}
 
-/// Resource to track when world needs re-meshing
+/// Resource to throttle re-meshing
 #[derive(Resource)]
 pub struct RenderState {
-    pub needs_update: bool,
     pub update_timer: Timer,
+    pub recolor_timer: Timer, // Checks for recoloured chunks in colour modes that change without voxels changing
 }
 
 impl Default for RenderState {
     fn default() -> Self {
         Self {
-            needs_update: true,
             update_timer: Timer::from_seconds(0.1, TimerMode::Repeating),
+            recolor_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Resource mapping each chunk with visible faces to its mesh entity
+#[derive(Resource, Default)]
+pub struct ChunkMeshes {
+    pub entities: HashMap<ChunkPos, Entity>,
+    pub colors: HashMap<ChunkPos, u64>, // Fingerprint of the voxel colours each mesh was built with
+    pub material: Handle<StandardMaterial>,
+}
+
+/// What the voxel colours show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    #[default]
+    Species,
+    Energy,
+    Age,
+    Generation,
+    Gene,
+    Light,
+    Nutrients,
+    Water,
+}
+
+impl ColorMode {
+    const ALL: [ColorMode; 8] = [
+        ColorMode::Species,
+        ColorMode::Energy,
+        ColorMode::Age,
+        ColorMode::Generation,
+        ColorMode::Gene,
+        ColorMode::Light,
+        ColorMode::Nutrients,
+        ColorMode::Water,
+    ];
+
+    /// The mode after this one, wrapping around
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorMode::Species => "Species",
+            ColorMode::Energy => "Plant Energy",
+            ColorMode::Age => "Plant Age",
+            ColorMode::Generation => "Generation",
+            ColorMode::Gene => "Gene",
+            ColorMode::Light => "Light Level",
+            ColorMode::Nutrients => "Soil Nutrients",
+            ColorMode::Water => "Soil Water",
+        }
+    }
+
+    /// Whether colours change while voxel types stay the same, so chunks must be recoloured periodically
+    /// Species and gene values are fixed for the life of a plant
+    pub fn is_dynamic(&self) -> bool {
+        !matches!(self, ColorMode::Species | ColorMode::Gene)
+    }
+}
+
+/// Resource holding the selected colour mode
+#[derive(Resource, Default)]
+pub struct ColorSettings {
+    pub mode: ColorMode,
+    pub gene: usize, // Index into `Genome::GENE_NAMES` of the gene shown by `ColorMode::Gene`
+}
+
+impl ColorSettings {
+    /// Name of the colour mode for the UI
+    pub fn label(&self) -> String {
+        match self.mode {
+            ColorMode::Gene => format!("Gene ({})", Genome::GENE_NAMES[self.gene]),
+            mode => mode.label().to_string(),
+        }
+    }
+}
+
+/// Number of shades in gradient colour modes; neighbouring faces of the same shade can still be merged
+const COLOR_STEPS: f32 = 16.0;
+
+/// Colours voxels for a colour mode
+pub struct VoxelColoring<'a> {
+    mode: ColorMode,
+    config: &'a SimConfig,
+    plant_values: HashMap<u32, f32>, // 0.0 to 1.0 for each plant id, for the plant colour modes
+}
+
+impl<'a> VoxelColoring<'a> {
+    pub fn new(mode: ColorMode, config: &'a SimConfig, plant_values: HashMap<u32, f32>) -> Self {
+        Self { mode, config, plant_values }
+    }
+
+    /// Colour of a voxel
+    /// Plant modes colour plants from blue (low) to red (high) and soil modes shade soil from pale to dark;
+    /// everything else keeps its normal colour
+    pub fn color(&self, voxel: &Voxel) -> Color {
+        let env = &voxel.environment;
+        match (self.mode, voxel.voxel_type) {
+            (ColorMode::Light, voxel_type) if voxel_type.is_solid() => gradient(
+                Color::srgb(0.05, 0.05, 0.15),
+                Color::srgb(1.0, 0.95, 0.6),
+                env.light_level / self.config.sunlight_max,
+            ),
+            (ColorMode::Nutrients, VoxelType::Soil) => gradient(
+                Color::srgb(0.75, 0.7, 0.55),
+                Color::srgb(0.2, 0.12, 0.05),
+                env.nutrients / self.config.soil_nutrient_max,
+            ),
+            (ColorMode::Water, VoxelType::Soil) => gradient(
+                Color::srgb(0.8, 0.7, 0.5),
+                Color::srgb(0.1, 0.2, 0.45),
+                env.water / self.config.soil_water_max,
+            ),
+            (
+                ColorMode::Energy | ColorMode::Age | ColorMode::Generation | ColorMode::Gene,
+                VoxelType::PlantMaterial { plant_id, .. },
+            ) => match self.plant_values.get(&plant_id) {
+                Some(value) => Color::hsl(240.0 * (1.0 - quantize(*value)), 0.8, 0.5),
+                None => voxel.voxel_type.get_color(),
+            },
+            (_, voxel_type) => voxel_type.get_color(),
+        }
+    }
+}
+
+fn quantize(value: f32) -> f32 {
+    (value.clamp(0.0, 1.0) * COLOR_STEPS).round() / COLOR_STEPS
+}
+
+/// Colour between `low` and `high` for a 0.0 to 1.0 value
+fn gradient(low: Color, high: Color, value: f32) -> Color {
+    let (low, high, t) = (low.to_srgba(), high.to_srgba(), quantize(value));
+    Color::srgb(
+        low.red + (high.red - low.red) * t,
+        low.green + (high.green - low.green) * t,
+        low.blue + (high.blue - low.blue) * t,
+    )
+}
+
+/// Value of each plant shown by the plant colour modes, scaled to 0.0 to 1.0 and keyed by plant id
+/// Energy, age and generation are relative to the highest in the population
+pub fn plant_color_values<'a>(
+    settings: &ColorSettings,
+    plants: impl Iterator<Item = (Entity, &'a PlantBiology, &'a GeneticLineage, &'a Genome)>,
+) -> HashMap<u32, f32> {
+    let raw: Vec<(u32, f32)> = plants
+        .filter_map(|(entity, biology, lineage, genome)| {
+            let value = match settings.mode {
+                ColorMode::Energy => biology.energy.max(0.0),
+                ColorMode::Age => biology.age,
+                ColorMode::Generation => lineage.generation as f32,
+                ColorMode::Gene => genome.gene_values()[settings.gene],
+                _ => return None,
+            };
+            Some((entity.index(), value))
+        })
+        .collect();
+
+    let scale = match settings.mode {
+        ColorMode::Gene => 1.0,
+        _ => raw.iter().map(|(_, value)| *value).fold(0.0, f32::max).max(f32::EPSILON),
+    };
+    raw.into_iter().map(|(id, value)| (id, value / scale)).collect()
+}
+
+/// Handle colour mode hotkeys
+/// C cycles the colour mode and G shows the next gene
+pub fn color_mode_hotkey_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<ColorSettings>,
+    mut world: ResMut<VoxelWorld>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyC) {
+        settings.mode = settings.mode.next();
+        world.mark_all_chunks_dirty();
+    }
+    if keyboard.just_pressed(KeyCode::KeyG) {
+        if settings.mode == ColorMode::Gene {
+            settings.gene = (settings.gene + 1) % Genome::GENE_NAMES.len();
         }
+        settings.mode = ColorMode::Gene;
+        world.mark_all_chunks_dirty();
     }
 }
 
 /// Setup rendering
+/// Chunk meshes are created by `update_world_mesh_system`, since every chunk starts dirty
 pub fn setup_rendering(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    world: Res<VoxelWorld>,
+    mut chunk_meshes: ResMut<ChunkMeshes>,
 ) {
-    // Create initial mesh
-    let mesh = create_world_mesh(&world);
-    let mesh_handle = meshes.add(mesh);
-
-    let material = materials.add(StandardMaterial {
+    chunk_meshes.material = materials.add(StandardMaterial {
         base_color: Color::WHITE,
         perceptual_roughness: 0.8,
         // Enable vertex colors so voxels show their actual colors
@@ -41,12 +240,6 @@ pub fn setup_rendering(
         ..default()
     });
 
-    commands.spawn((
-        Mesh3d(mesh_handle),
-        MeshMaterial3d(material),
-        WorldMesh,
-    ));
-
     // Add lighting
     commands.spawn((
         DirectionalLight {
@@ -65,195 +258,315 @@ pub fn setup_rendering(
     });
 }
 
-/// Update mesh when world changes
+/// Re-mesh the chunks whose voxels changed since the last update
 pub fn update_world_mesh_system(
+    mut commands: Commands,
     mut state: ResMut<RenderState>,
-    world: Res<VoxelWorld>,
+    mut world: ResMut<VoxelWorld>,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut chunk_meshes: ResMut<ChunkMeshes>,
     query: Query<&Mesh3d, With<WorldMesh>>,
+    plants: Query<(Entity, &PlantBiology, &GeneticLineage, &Genome)>,
+    settings: Res<ColorSettings>,
+    config: Res<SimConfig>,
     time: Res<Time>,
 ) {
     state.update_timer.tick(time.delta());
+    state.recolor_timer.tick(time.delta());
+
+    // Colours in dynamic modes drift without voxels changing; remesh only the chunks whose colours did
+    if settings.mode.is_dynamic() && state.recolor_timer.just_finished() {
+        let coloring = VoxelColoring::new(settings.mode, &config, plant_color_values(&settings, plants.iter()));
+        let recolored: Vec<ChunkPos> = chunk_meshes
+            .colors
+            .iter()
+            .filter(|(chunk, colors)| chunk_color_fingerprint(&world, chunk, |voxel| coloring.color(voxel)) != **colors)
+            .map(|(chunk, _)| *chunk)
+            .collect();
+        for chunk in &recolored {
+            world.mark_chunk_dirty(chunk);
+        }
+    }
 
     if !state.update_timer.just_finished() {
         return;
     }
 
-    state.needs_update = true;
+    let dirty = world.take_dirty_chunks();
+    if dirty.is_empty() {
+        return;
+    }
+    let coloring = VoxelColoring::new(settings.mode, &config, plant_color_values(&settings, plants.iter()));
+
+    for chunk in dirty {
+        let existing = chunk_meshes.entities.get(&chunk).copied();
+        let mesh = create_chunk_mesh(&world, &chunk, config.meshing_mode, |voxel| coloring.color(voxel));
+        if mesh.is_some() {
+            let colors = chunk_color_fingerprint(&world, &chunk, |voxel| coloring.color(voxel));
+            chunk_meshes.colors.insert(chunk, colors);
+        } else {
+            chunk_meshes.colors.remove(&chunk);
+        }
 
-    if state.needs_update {
-        for mesh_handle in query.iter() {
-            if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
-                *mesh = create_world_mesh(&world);
+        match (mesh, existing) {
+            // Replace the mesh of a chunk that is already drawn
+            (Some(mesh), Some(entity)) => {
+                if let Some(old_mesh) = query.get(entity).ok().and_then(|mesh3d| meshes.get_mut(&mesh3d.0)) {
+                    *old_mesh = mesh;
+                }
+            }
+            (Some(mesh), None) => {
+                let entity = commands
+                    .spawn((
+                        Mesh3d(meshes.add(mesh)),
+                        MeshMaterial3d(chunk_meshes.material.clone()),
+                        WorldMesh { chunk },
+                    ))
+                    .id();
+                chunk_meshes.entities.insert(chunk, entity);
             }
+            // The chunk no longer has any visible faces
+            (None, Some(entity)) => {
+                commands.entity(entity).despawn();
+                chunk_meshes.entities.remove(&chunk);
+            }
+            (None, None) => {}
         }
-        state.needs_update = false;
     }
 }
 
-/// Create a mesh from the voxel world
-fn create_world_mesh(world: &VoxelWorld) -> Mesh {
-    let mut positions = Vec::new();
-    let mut normals = Vec::new();
-    let mut uvs = Vec::new();
-    let mut indices = Vec::new();
-    let mut colors = Vec::new();
-
-    // Iterate through all voxels and create faces for solid ones
-    for pos in world.iter_positions().collect::<Vec<_>>() {
-        if let Some(voxel) = world.get(&pos) {
-            if !voxel.voxel_type.is_solid() {
-                continue;
-            }
+/// Despawn every chunk mesh, e.g. when the voxel world is replaced by a loaded snapshot
+/// The new world's chunks start out dirty, so they are all meshed again on the next update
+pub fn clear_chunk_meshes(world: &mut World) {
+    let Some(mut chunk_meshes) = world.get_resource_mut::<ChunkMeshes>() else {
+        return;
+    };
+    chunk_meshes.entities.clear();
+    chunk_meshes.colors.clear();
 
-            let color = voxel.voxel_type.get_color();
-            let world_pos = pos.to_world_pos();
-
-            // Check each face
-            add_voxel_faces(
-                &pos,
-                &world_pos,
-                &color,
-                world,
-                &mut positions,
-                &mut normals,
-                &mut uvs,
-                &mut colors,
-                &mut indices,
-            );
-        }
+    let entities: Vec<Entity> = world
+        .query_filtered::<Entity, With<WorldMesh>>()
+        .iter(world)
+        .collect();
+    for entity in entities {
+        world.despawn(entity);
     }
+}
 
-    // Create mesh from collected voxel data
-    if positions.is_empty() {
-        // If no voxels, return a simple ground plane
-        return Mesh::from(Plane3d::default().mesh().size(
-            WORLD_WIDTH as f32 * VOXEL_SIZE,
-            WORLD_DEPTH as f32 * VOXEL_SIZE,
-        ));
+/// Hash of the colours of a chunk's solid voxels, to tell whether its mesh needs recolouring
+/// Colours are compared at 8 bits per channel, as they end up in the mesh
+pub fn chunk_color_fingerprint(world: &VoxelWorld, chunk: &ChunkPos, color: impl Fn(&Voxel) -> Color) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let origin = chunk.origin();
+
+    for y in origin.y..origin.y + CHUNK_SIZE as i32 {
+        for z in origin.z..origin.z + CHUNK_SIZE as i32 {
+            for x in origin.x..origin.x + CHUNK_SIZE as i32 {
+                match world.get(&VoxelPos::new(x, y, z)) {
+                    Some(voxel) if voxel.voxel_type.is_solid() => {
+                        let rgba = color(voxel).to_srgba();
+                        hasher.write(&[rgba.red, rgba.green, rgba.blue].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+                    }
+                    _ => hasher.write_u8(0),
+                }
+            }
+        }
     }
 
-    // Start with a plane mesh and replace its data
-    let mut mesh = Mesh::from(Plane3d::default().mesh().size(1.0, 1.0));
+    hasher.finish()
+}
+
+/// Create a mesh from the voxels of one chunk, in world coordinates
+/// Naive meshing draws every visible voxel face as its own quad; greedy meshing merges
+/// neighbouring coplanar faces of the same colour into larger rectangles
+/// Returns `None` if the chunk has no visible faces
+pub fn create_chunk_mesh(
+    world: &VoxelWorld,
+    chunk: &ChunkPos,
+    mode: MeshingMode,
+    color: impl Fn(&Voxel) -> Color,
+) -> Option<Mesh> {
+    let mut builder = MeshBuilder::default();
+    let origin = chunk.origin();
+
+    for face in FACES {
+        // Sweep the chunk one slice at a time along the face normal
+        for d in 0..CHUNK_SIZE as i32 {
+            let mut mask = face_mask(world, &origin, face, d, &color);
 
-    // Replace all attributes with our voxel data
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+            for v in 0..CHUNK_SIZE {
+                let mut u = 0;
+                while u < CHUNK_SIZE {
+                    let Some(color) = mask[u + v * CHUNK_SIZE] else {
+                        u += 1;
+                        continue;
+                    };
+
+                    // Grow the quad along u while the colour matches, then along v while whole rows match
+                    let (width, height) = match mode {
+                        MeshingMode::Naive => (1, 1),
+                        MeshingMode::Greedy => {
+                            let width = (u..CHUNK_SIZE)
+                                .take_while(|&du| mask[du + v * CHUNK_SIZE] == Some(color))
+                                .count();
+                            let height = (v..CHUNK_SIZE)
+                                .take_while(|&dv| (u..u + width).all(|du| mask[du + dv * CHUNK_SIZE] == Some(color)))
+                                .count();
+                            (width, height)
+                        }
+                    };
+
+                    for dv in v..v + height {
+                        for du in u..u + width {
+                            mask[du + dv * CHUNK_SIZE] = None;
+                        }
+                    }
+                    builder.add_quad(&origin, face, d, [u, v, width, height], color);
+                    u += width;
+                }
+            }
+        }
+    }
 
-    // Set indices
-    mesh.insert_indices(bevy::mesh::Indices::U32(indices));
+    builder.build()
+}
 
-    mesh
+/// One of the six face directions of a voxel
+#[derive(Clone, Copy)]
+struct Face {
+    axis: usize,    // Axis of the face normal: 0 = x, 1 = y, 2 = z
+    positive: bool, // Whether the face points toward +axis
 }
 
-/// Add faces for a voxel
-fn add_voxel_faces(
-    pos: &VoxelPos,
-    world_pos: &Vec3,
-    color: &Color,
-    world: &VoxelWorld,
-    positions: &mut Vec<[f32; 3]>,
-    normals: &mut Vec<[f32; 3]>,
-    uvs: &mut Vec<[f32; 2]>,
-    colors: &mut Vec<[f32; 4]>,
-    indices: &mut Vec<u32>,
-) {
-    let s = VOXEL_SIZE / 2.0;
-    let c = [color.to_srgba().red, color.to_srgba().green, color.to_srgba().blue, color.to_srgba().alpha];
-
-    // Check each direction and add face if neighbor is empty
-    let neighbors = pos.neighbors();
-
-    // Top face (counter-clockwise when viewed from above)
-    if should_render_face(&neighbors[2], world) {
-        let base = positions.len() as u32;
-        positions.extend_from_slice(&[
-            [world_pos.x - s, world_pos.y + s, world_pos.z + s],
-            [world_pos.x + s, world_pos.y + s, world_pos.z + s],
-            [world_pos.x + s, world_pos.y + s, world_pos.z - s],
-            [world_pos.x - s, world_pos.y + s, world_pos.z - s],
-        ]);
-        normals.extend_from_slice(&[[0.0, 1.0, 0.0]; 4]);
-        uvs.extend_from_slice(&[[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]]);
-        colors.extend_from_slice(&[c; 4]);
-        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+const FACES: [Face; 6] = [
+    Face { axis: 0, positive: true },
+    Face { axis: 0, positive: false },
+    Face { axis: 1, positive: true },
+    Face { axis: 1, positive: false },
+    Face { axis: 2, positive: true },
+    Face { axis: 2, positive: false },
+];
+
+impl Face {
+    /// Axes spanning the face plane, ordered so that u × v points along +axis
+    fn plane_axes(&self) -> (usize, usize) {
+        ((self.axis + 1) % 3, (self.axis + 2) % 3)
     }
 
-    // Bottom face (counter-clockwise when viewed from below)
-    if should_render_face(&neighbors[3], world) {
-        let base = positions.len() as u32;
-        positions.extend_from_slice(&[
-            [world_pos.x - s, world_pos.y - s, world_pos.z - s],
-            [world_pos.x + s, world_pos.y - s, world_pos.z - s],
-            [world_pos.x + s, world_pos.y - s, world_pos.z + s],
-            [world_pos.x - s, world_pos.y - s, world_pos.z + s],
-        ]);
-        normals.extend_from_slice(&[[0.0, -1.0, 0.0]; 4]);
-        uvs.extend_from_slice(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
-        colors.extend_from_slice(&[c; 4]);
-        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    /// Position in a chunk of the voxel at depth `d` along the normal and (u, v) in the face plane
+    fn voxel_at(&self, origin: &VoxelPos, d: i32, u: i32, v: i32) -> VoxelPos {
+        let (u_axis, v_axis) = self.plane_axes();
+        let mut coords = [0; 3];
+        coords[self.axis] = d;
+        coords[u_axis] = u;
+        coords[v_axis] = v;
+        VoxelPos::new(origin.x + coords[0], origin.y + coords[1], origin.z + coords[2])
     }
 
-    // Front face (+Z) (counter-clockwise when viewed from front)
-    if should_render_face(&neighbors[1], world) {
-        let base = positions.len() as u32;
-        positions.extend_from_slice(&[
-            [world_pos.x - s, world_pos.y - s, world_pos.z + s],
-            [world_pos.x + s, world_pos.y - s, world_pos.z + s],
-            [world_pos.x + s, world_pos.y + s, world_pos.z + s],
-            [world_pos.x - s, world_pos.y + s, world_pos.z + s],
-        ]);
-        normals.extend_from_slice(&[[0.0, 0.0, 1.0]; 4]);
-        uvs.extend_from_slice(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
-        colors.extend_from_slice(&[c; 4]);
-        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    fn normal(&self) -> [f32; 3] {
+        let mut normal = [0.0; 3];
+        normal[self.axis] = if self.positive { 1.0 } else { -1.0 };
+        normal
     }
+}
 
-    // Back face (-Z) (counter-clockwise when viewed from back)
-    if should_render_face(&neighbors[0], world) {
-        let base = positions.len() as u32;
-        positions.extend_from_slice(&[
-            [world_pos.x - s, world_pos.y - s, world_pos.z - s],
-            [world_pos.x + s, world_pos.y - s, world_pos.z - s],
-            [world_pos.x + s, world_pos.y + s, world_pos.z - s],
-            [world_pos.x - s, world_pos.y + s, world_pos.z - s],
-        ]);
-        normals.extend_from_slice(&[[0.0, 0.0, -1.0]; 4]);
-        uvs.extend_from_slice(&[[1.0, 0.0], [0.0, 0.0], [0.0, 1.0], [1.0, 1.0]]);
-        colors.extend_from_slice(&[c; 4]);
-        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+/// Colours of the visible faces in one slice of a chunk, indexed by `u + v * CHUNK_SIZE`
+/// A face is visible when its voxel is solid and the voxel it faces is air or outside the world
+fn face_mask(
+    world: &VoxelWorld,
+    origin: &VoxelPos,
+    face: Face,
+    d: i32,
+    color: &impl Fn(&Voxel) -> Color,
+) -> Vec<Option<[f32; 4]>> {
+    let step = if face.positive { 1 } else { -1 };
+    let mut mask = vec![None; CHUNK_SIZE * CHUNK_SIZE];
+
+    for v in 0..CHUNK_SIZE {
+        for u in 0..CHUNK_SIZE {
+            let pos = face.voxel_at(origin, d, u as i32, v as i32);
+            let Some(voxel) = world.get(&pos) else {
+                continue;
+            };
+            if !voxel.voxel_type.is_solid() {
+                continue;
+            }
+
+            let neighbor = face.voxel_at(origin, d + step, u as i32, v as i32);
+            if should_render_face(&neighbor, world) {
+                let color = color(voxel).to_srgba();
+                mask[u + v * CHUNK_SIZE] = Some([color.red, color.green, color.blue, color.alpha]);
+            }
+        }
     }
 
-    // Right face (+X) (counter-clockwise when viewed from right)
-    if should_render_face(&neighbors[5], world) {
-        let base = positions.len() as u32;
-        positions.extend_from_slice(&[
-            [world_pos.x + s, world_pos.y - s, world_pos.z + s],
-            [world_pos.x + s, world_pos.y - s, world_pos.z - s],
-            [world_pos.x + s, world_pos.y + s, world_pos.z - s],
-            [world_pos.x + s, world_pos.y + s, world_pos.z + s],
+    mask
+}
+
+/// Vertex data collected for a chunk mesh
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    colors: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    /// Add a quad covering `[u, v, width, height]` voxel faces in slice `d` of a chunk
+    fn add_quad(&mut self, origin: &VoxelPos, face: Face, d: i32, rect: [usize; 4], color: [f32; 4]) {
+        let [u, v, width, height] = rect.map(|n| n as i32);
+        // The face lies on the voxel's far side when it points toward +axis
+        let plane = if face.positive { d + 1 } else { d };
+
+        // Voxels are centred on their position, so their corners are half a voxel away
+        let s = VOXEL_SIZE / 2.0;
+        let corner = |u: i32, v: i32| {
+            let pos = face.voxel_at(origin, plane, u, v);
+            let world_pos = pos.to_world_pos() - Vec3::splat(s);
+            [world_pos.x, world_pos.y, world_pos.z]
+        };
+
+        let base = self.positions.len() as u32;
+        self.positions.extend_from_slice(&[
+            corner(u, v),
+            corner(u + width, v),
+            corner(u + width, v + height),
+            corner(u, v + height),
         ]);
-        normals.extend_from_slice(&[[1.0, 0.0, 0.0]; 4]);
-        uvs.extend_from_slice(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
-        colors.extend_from_slice(&[c; 4]);
-        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        self.normals.extend_from_slice(&[face.normal(); 4]);
+        // UVs tile once per voxel so merged quads keep the voxel scale
+        let (w, h) = (width as f32, height as f32);
+        self.uvs.extend_from_slice(&[[0.0, 0.0], [w, 0.0], [w, h], [0.0, h]]);
+        self.colors.extend_from_slice(&[color; 4]);
+
+        // Counter-clockwise when viewed from outside the voxel
+        if face.positive {
+            self.indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        } else {
+            self.indices.extend_from_slice(&[base, base + 2, base + 1, base, base + 3, base + 2]);
+        }
     }
 
-    // Left face (-X) (counter-clockwise when viewed from left)
-    if should_render_face(&neighbors[4], world) {
-        let base = positions.len() as u32;
-        positions.extend_from_slice(&[
-            [world_pos.x - s, world_pos.y - s, world_pos.z - s],
-            [world_pos.x - s, world_pos.y - s, world_pos.z + s],
-            [world_pos.x - s, world_pos.y + s, world_pos.z + s],
-            [world_pos.x - s, world_pos.y + s, world_pos.z - s],
-        ]);
-        normals.extend_from_slice(&[[-1.0, 0.0, 0.0]; 4]);
-        uvs.extend_from_slice(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
-        colors.extend_from_slice(&[c; 4]);
-        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    fn build(self) -> Option<Mesh> {
+        if self.positions.is_empty() {
+            return None;
+        }
+
+        // Start with a plane mesh and replace its data
+        let mut mesh = Mesh::from(Plane3d::default().mesh().size(1.0, 1.0));
+
+        // Replace all attributes with our voxel data
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+
+        // Set indices
+        mesh.insert_indices(bevy::mesh::Indices::U32(self.indices));
+
+        Some(mesh)
     }
 }
 
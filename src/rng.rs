@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use std::ops::{Deref, DerefMut};
+use crate::config::*;
+
+/// Resource holding the random number generator shared by all stochastic systems
+/// The same seed and config reproduce the same run
+#[derive(Resource)]
+pub struct SimulationRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl SimulationRng {
+    /// Create a generator from a fixed seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Create a generator from a fixed seed, or a random one if none is given
+    pub fn from_seed_or_random(seed: Option<u64>) -> Self {
+        Self::new(seed.unwrap_or_else(|| rand::rng().random()))
+    }
+
+    /// Seed this generator was created from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for SimulationRng {
+    fn default() -> Self {
+        Self::from_seed_or_random(RNG_SEED)
+    }
+}
+
+impl Deref for SimulationRng {
+    type Target = StdRng;
+
+    fn deref(&self) -> &StdRng {
+        &self.rng
+    }
+}
+
+impl DerefMut for SimulationRng {
+    fn deref_mut(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+}
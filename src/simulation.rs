@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::time::Duration;
+use crate::config::*;
+use crate::world::*;
+use crate::plant::*;
+use crate::statistics::{
+    StatisticsHistory, GenerationStats, StatsExporter, collect_statistics_system, export_statistics_system,
+};
+use crate::rng::SimulationRng;
+use crate::snapshot::restore_pending_snapshot_system;
+
+/// Plugin registering the simulation resources and systems shared by windowed and headless runs
+/// Insert a `SimConfig` before adding the plugin to override the defaults
+pub struct SimulationPlugin;
+
+/// Ordered stages of a simulation tick, so stochastic systems always draw
+/// from the RNG in the same order
+/// All stages run in `FixedUpdate` at `SimConfig::ticks_per_second`, independent of frame rate
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimulationSet {
+    Environment,
+    Plants,
+    Statistics,
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimConfig>();
+        let config = app.world().resource::<SimConfig>().clone();
+
+        // Terrain shares the run's seed so the whole run is reproducible
+        let sim_rng = SimulationRng::from_seed_or_random(config.seed);
+        let terrain_seed = terrain::terrain_seed(sim_rng.seed());
+
+        app
+            // Resources
+            .insert_resource(VoxelWorld::from_config(&config, terrain_seed))
+            .insert_resource(DayNightCycle {
+                day_length: config.day_length,
+                ..default()
+            })
+            .insert_resource(YearCycle {
+                year_length: config.year_length,
+                ..default()
+            })
+            .insert_resource(Weather::default())
+            .insert_resource(TemperatureField::default())
+            .insert_resource(StatisticsHistory::new(config.stats_update_interval))
+            .insert_resource(GenerationStats::default())
+            .insert_resource(SimulationState::default())
+            .insert_resource(SimulationClock::default())
+            .insert_resource(SpeciesCounter { next_id: config.initial_seed_count as u32 })
+            .insert_resource(sim_rng)
+            .insert_resource(Time::<Fixed>::from_duration(tick_duration(&config)))
+            .configure_sets(FixedUpdate, (
+                SimulationSet::Environment,
+                SimulationSet::Plants,
+                SimulationSet::Statistics,
+            ).chain())
+            // Startup systems (a snapshot given on the command line replaces the initial plants)
+            .add_systems(PreStartup, restore_pending_snapshot_system)
+            .add_systems(Startup, spawn_initial_plants)
+            // Environment systems
+            .add_systems(FixedUpdate, (
+                update_day_night_system,
+                update_year_cycle_system,
+                update_temperature_system,
+                update_light_system,
+                update_weather_system,
+                hydrology_system,
+                regenerate_resources_system,
+                decompose_litter_system,
+            ).chain().in_set(SimulationSet::Environment).run_if(simulation_running))
+            // Plant systems
+            .add_systems(FixedUpdate, (
+                plant_growth_system,
+                structural_support_system,
+                phenology_system,
+                photosynthesis_system,
+                frost_damage_system,
+                resource_absorption_system,
+                storage_system,
+                maintenance_cost_system,
+                aging_system,
+                reproduction_system,
+                cleanup_dead_plants_system,
+            ).chain().in_set(SimulationSet::Plants).run_if(simulation_running))
+            // Statistics
+            .add_systems(FixedUpdate, (
+                collect_statistics_system,
+                export_statistics_system.run_if(resource_exists::<StatsExporter>),
+            ).chain().in_set(SimulationSet::Statistics))
+            // Time control
+            .add_systems(FixedUpdate, (
+                season_stop_system.after(SimulationSet::Environment).before(SimulationSet::Plants),
+                finish_step_system.after(SimulationSet::Statistics),
+            ))
+            .add_systems(Update, apply_time_scale_system)
+            // Control systems (only when keyboard input is available)
+            .add_systems(Update, (
+                pause_system,
+                time_control_keyboard_system,
+            ).run_if(resource_exists::<ButtonInput<KeyCode>>));
+    }
+}
+
+/// Length of one simulation tick
+pub fn tick_duration(config: &SimConfig) -> Duration {
+    Duration::from_secs_f64(1.0 / config.ticks_per_second as f64)
+}
+
+/// Spawn initial plants, unless plants were already restored from a snapshot
+pub fn spawn_initial_plants(
+    mut commands: Commands,
+    mut world: ResMut<VoxelWorld>,
+    mut sim_rng: ResMut<SimulationRng>,
+    config: Res<SimConfig>,
+    existing: Query<(), With<PlantBiology>>,
+) {
+    println!("Simulation seed: {}", sim_rng.seed());
+    if !existing.is_empty() {
+        return;
+    }
+
+    let rng = &mut **sim_rng;
+
+    for species_id in 0..config.initial_seed_count as u32 {
+        // Find a random surface position
+        let x = rng.random_range(0..world.width()) as i32;
+        let z = rng.random_range(0..world.depth()) as i32;
+        let Some(y) = world.surface_height(x, z) else {
+            continue;
+        };
+
+        let pos = VoxelPos::new(x, y, z);
+
+        // Check if it's a valid position, not buried under another plant's seed
+        let above = VoxelPos::new(x, y + 1, z);
+        let open_above = world.get(&above).is_some_and(|voxel| voxel.voxel_type.is_air());
+        if let Some(voxel) = world.get(&pos) {
+            if matches!(voxel.voxel_type, VoxelType::Soil) && open_above {
+                let genome = Genome::random(rng);
+                spawn_plant(&mut commands, &mut world, pos, genome, 0, None, species_id);
+            }
+        }
+    }
+
+    println!("Spawned {} initial plants", config.initial_seed_count);
+}
+
+/// Resource to track simulation pause state and speed
+#[derive(Resource)]
+pub struct SimulationState {
+    pub paused: bool,
+    pub time_scale: f32,                      // Simulated seconds per real second
+    pub step_requested: bool,                 // Run a single tick while paused
+    pub run_until_season: Option<&'static str>, // Pause when this season ends
+}
+
+impl Default for SimulationState {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            time_scale: 1.0,
+            step_requested: false,
+            run_until_season: None,
+        }
+    }
+}
+
+impl SimulationState {
+    /// Double the simulation speed, up to `MAX_TIME_SCALE`
+    pub fn speed_up(&mut self) {
+        self.time_scale = (self.time_scale * 2.0).min(MAX_TIME_SCALE);
+    }
+
+    /// Halve the simulation speed, down to `MIN_TIME_SCALE`
+    pub fn slow_down(&mut self) {
+        self.time_scale = (self.time_scale / 2.0).max(MIN_TIME_SCALE);
+    }
+
+    /// Advance a single tick (only while paused)
+    pub fn step(&mut self) {
+        if self.paused {
+            self.step_requested = true;
+        }
+    }
+
+    /// Resume and pause again when the given season ends
+    pub fn run_until_next_season(&mut self, current_season: &'static str) {
+        self.run_until_season = Some(current_season);
+        self.paused = false;
+    }
+}
+
+/// Resource holding the simulated seconds that passed before this app started, such as in a loaded snapshot
+/// `Time` always starts from zero, so anything reporting the run's time adds this offset
+#[derive(Resource, Default)]
+pub struct SimulationClock {
+    pub offset: f32,
+}
+
+impl SimulationClock {
+    /// Simulated seconds since the run began, including time restored from a snapshot
+    pub fn elapsed_secs(&self, time: &Time) -> f32 {
+        self.offset + time.elapsed_secs()
+    }
+}
+
+/// Condition to check if simulation is running
+pub fn simulation_running(state: Res<SimulationState>) -> bool {
+    !state.paused || state.step_requested
+}
+
+/// System to apply the chosen time scale to virtual time
+fn apply_time_scale_system(state: Res<SimulationState>, mut time: ResMut<Time<Virtual>>) {
+    if state.is_changed() {
+        time.set_relative_speed(state.time_scale);
+    }
+}
+
+/// System to clear a single-step request once its tick has run
+fn finish_step_system(mut state: ResMut<SimulationState>) {
+    if state.step_requested {
+        state.step_requested = false;
+    }
+}
+
+/// System to pause the simulation when a "run until next season" target is reached
+fn season_stop_system(mut state: ResMut<SimulationState>, year_cycle: Res<YearCycle>) {
+    let Some(season) = state.run_until_season else {
+        return;
+    };
+
+    if get_season_name(&year_cycle) != season {
+        state.run_until_season = None;
+        state.paused = true;
+        println!("Simulation paused at start of {}", get_season_name(&year_cycle));
+    }
+}
+
+/// System to handle speed, single-step and season keys
+fn time_control_keyboard_system(
+    mut state: ResMut<SimulationState>,
+    year_cycle: Res<YearCycle>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    if keyboard.just_pressed(KeyCode::BracketRight) {
+        state.speed_up();
+        println!("Simulation speed: {}x", state.time_scale);
+    }
+    if keyboard.just_pressed(KeyCode::BracketLeft) {
+        state.slow_down();
+        println!("Simulation speed: {}x", state.time_scale);
+    }
+    if keyboard.just_pressed(KeyCode::Period) {
+        state.step();
+    }
+    if keyboard.just_pressed(KeyCode::KeyN) {
+        state.run_until_next_season(get_season_name(&year_cycle));
+    }
+}
+
+/// System to handle pause/resume
+pub fn pause_system(
+    mut state: ResMut<SimulationState>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyP) {
+        state.paused = !state.paused;
+        if state.paused {
+            println!("Simulation paused");
+        } else {
+            println!("Simulation resumed");
+        }
+    }
+}
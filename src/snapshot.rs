@@ -0,0 +1,264 @@
+use bevy::prelude::*;
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+use crate::config::*;
+use crate::world::{VoxelWorld, DayNightCycle, YearCycle, Weather};
+use crate::plant::{PlantBiology, PlantStructure, GrowthTimer, Genome, GeneticLineage, SpeciesCounter};
+use crate::simulation::SimulationClock;
+use crate::statistics::{StatisticsHistory, StatsSnapshot};
+
+/// Magic bytes at the start of every snapshot file
+const SNAPSHOT_MAGIC: &[u8; 4] = b"PESS";
+
+/// Current snapshot format version, bumped whenever the saved data changes
+pub const SNAPSHOT_VERSION: u32 = 13;
+
+/// Saved state of a single plant
+#[derive(Serialize, Deserialize)]
+struct PlantSnapshot {
+    plant_id: u32, // Entity index when saved, as stored in `VoxelType::PlantMaterial`
+    biology: PlantBiology,
+    structure: PlantStructure,
+    genome: Genome,
+    generation: u32,
+    parent_id: Option<u32>,
+    species_id: u32,
+    growth_elapsed: f32,
+}
+
+/// Complete saved state of a simulation
+/// The RNG state is not saved, so a loaded run continues with the current seed
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub elapsed: f32, // Simulated seconds when the snapshot was taken
+    world: VoxelWorld,
+    day_night: DayNightCycle,
+    year_cycle: YearCycle,
+    weather: Weather,
+    next_species_id: u32,
+    stats_snapshots: Vec<StatsSnapshot>,
+    stats_elapsed: f32,
+    plants: Vec<PlantSnapshot>,
+}
+
+/// Resource holding a snapshot to restore when the app starts
+#[derive(Resource)]
+pub struct PendingSnapshot(pub Snapshot);
+
+impl Snapshot {
+    /// Capture the current simulation state
+    pub fn capture(world: &mut World) -> Self {
+        let mut query = world.query::<(
+            Entity,
+            &PlantBiology,
+            &PlantStructure,
+            &Genome,
+            &GeneticLineage,
+            &GrowthTimer,
+        )>();
+
+        let mut plants: Vec<PlantSnapshot> = query
+            .iter(world)
+            .map(|(entity, biology, structure, genome, lineage, growth_timer)| PlantSnapshot {
+                plant_id: entity.index(),
+                biology: biology.clone(),
+                structure: structure.clone(),
+                genome: genome.clone(),
+                generation: lineage.generation,
+                parent_id: lineage.parent_id.map(|parent| parent.index()),
+                species_id: lineage.species_id,
+                growth_elapsed: growth_timer.timer.elapsed_secs(),
+            })
+            .collect();
+
+        // Keep a stable order so identical states produce identical files
+        plants.sort_by_key(|plant| plant.plant_id);
+
+        let stats = world.resource::<StatisticsHistory>();
+
+        Self {
+            elapsed: world.resource::<SimulationClock>().elapsed_secs(world.resource::<Time>()),
+            world: world.resource::<VoxelWorld>().clone(),
+            day_night: world.resource::<DayNightCycle>().clone(),
+            year_cycle: world.resource::<YearCycle>().clone(),
+            weather: world.resource::<Weather>().clone(),
+            next_species_id: world.resource::<SpeciesCounter>().next_id,
+            stats_snapshots: stats.snapshots.clone(),
+            stats_elapsed: stats.update_timer.elapsed_secs(),
+            plants,
+        }
+    }
+
+    /// Replace the current simulation state with this snapshot
+    pub fn restore(self, world: &mut World) {
+        // Remove the current plants
+        let existing: Vec<Entity> = world
+            .query_filtered::<Entity, With<PlantBiology>>()
+            .iter(world)
+            .collect();
+        for entity in existing {
+            world.despawn(entity);
+        }
+
+        // Spawn saved plants, remembering their new entities
+        let mut entities = HashMap::new();
+        let mut parents = Vec::new();
+
+        for plant in self.plants {
+            let mut growth_timer = GrowthTimer::default();
+            growth_timer
+                .timer
+                .set_elapsed(Duration::from_secs_f32(plant.growth_elapsed));
+
+            let entity = world
+                .spawn((
+                    plant.biology,
+                    plant.structure,
+                    plant.genome,
+                    GeneticLineage {
+                        generation: plant.generation,
+                        parent_id: None,
+                        species_id: plant.species_id,
+                    },
+                    growth_timer,
+                ))
+                .id();
+
+            entities.insert(plant.plant_id, entity);
+            parents.push((entity, plant.parent_id));
+        }
+
+        // Reconnect lineage for parents that are still alive
+        for (entity, parent_id) in parents {
+            let parent = parent_id.and_then(|id| entities.get(&id).copied());
+            if let Some(mut lineage) = world.get_mut::<GeneticLineage>(entity) {
+                lineage.parent_id = parent;
+            }
+        }
+
+        // Point plant voxels at the new entities
+        let mut voxel_world = self.world;
+        voxel_world.remap_plant_ids(|old_id| entities.get(&old_id).map(|entity| entity.index()));
+
+        world.insert_resource(voxel_world);
+        world.insert_resource(self.day_night);
+        world.insert_resource(self.year_cycle);
+        world.insert_resource(self.weather);
+        world.insert_resource(SpeciesCounter { next_id: self.next_species_id });
+
+        // Continue the clock from the saved time rather than from whenever the snapshot was loaded
+        let offset = self.elapsed - world.resource::<Time>().elapsed_secs();
+        world.insert_resource(SimulationClock { offset });
+
+        let mut stats = world.resource_mut::<StatisticsHistory>();
+        stats.snapshots = self.stats_snapshots;
+        stats
+            .update_timer
+            .set_elapsed(Duration::from_secs_f32(self.stats_elapsed));
+    }
+
+    /// Write the snapshot to a compressed, versioned file
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Failed to create snapshot {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+
+        writer
+            .write_all(SNAPSHOT_MAGIC)
+            .and_then(|_| writer.write_all(&SNAPSHOT_VERSION.to_le_bytes()))
+            .map_err(|e| format!("Failed to write snapshot {}: {}", path.display(), e))?;
+
+        let mut encoder = ZlibEncoder::new(writer, Compression::default());
+        bincode::serde::encode_into_std_write(self, &mut encoder, bincode::config::standard())
+            .map_err(|e| format!("Failed to encode snapshot {}: {}", path.display(), e))?;
+        encoder
+            .finish()
+            .and_then(|mut writer| writer.flush())
+            .map_err(|e| format!("Failed to write snapshot {}: {}", path.display(), e))
+    }
+
+    /// Read a snapshot written by `save`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| format!("Failed to open snapshot {}: {}", path.display(), e))?;
+        let mut reader = BufReader::new(file);
+
+        let mut header = [0u8; 8];
+        reader
+            .read_exact(&mut header)
+            .map_err(|e| format!("Failed to read snapshot {}: {}", path.display(), e))?;
+
+        if &header[0..4] != SNAPSHOT_MAGIC {
+            return Err(format!("{} is not a simulation snapshot", path.display()));
+        }
+
+        let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        if version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "Snapshot {} has version {}, but this build reads version {}",
+                path.display(),
+                version,
+                SNAPSHOT_VERSION
+            ));
+        }
+
+        let mut decoder = ZlibDecoder::new(reader);
+        bincode::serde::decode_from_std_read(&mut decoder, bincode::config::standard())
+            .map_err(|e| format!("Failed to decode snapshot {}: {}", path.display(), e))
+    }
+}
+
+/// Plugin for quick-save and quick-load hotkeys
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        // The web build has no file system to save to
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, snapshot_hotkey_system);
+    }
+}
+
+/// System to restore a snapshot given on the command line
+pub fn restore_pending_snapshot_system(world: &mut World) {
+    if let Some(PendingSnapshot(snapshot)) = world.remove_resource::<PendingSnapshot>() {
+        let elapsed = snapshot.elapsed;
+        snapshot.restore(world);
+        println!("Restored snapshot taken at {:.1}s", elapsed);
+    }
+}
+
+/// System to save (F5) or load (F9) the quick-save snapshot
+#[cfg(not(target_arch = "wasm32"))]
+fn snapshot_hotkey_system(mut commands: Commands, keyboard: Res<ButtonInput<KeyCode>>) {
+    if keyboard.just_pressed(KeyCode::F5) {
+        commands.queue(|world: &mut World| {
+            let path = Path::new(QUICKSAVE_PATH);
+            match Snapshot::capture(world).save(path) {
+                Ok(()) => println!("Saved snapshot to {}", path.display()),
+                Err(e) => eprintln!("{}", e),
+            }
+        });
+    }
+
+    if keyboard.just_pressed(KeyCode::F9) {
+        commands.queue(|world: &mut World| {
+            let path = Path::new(QUICKSAVE_PATH);
+            match Snapshot::load(path) {
+                Ok(snapshot) => {
+                    snapshot.restore(world);
+                    // Meshes of chunks the loaded world may not have would otherwise stay on screen
+                    crate::rendering::clear_chunk_meshes(world);
+                    println!("Loaded snapshot from {}", path.display());
+                }
+                Err(e) => eprintln!("{}", e),
+            }
+        });
+    }
+}
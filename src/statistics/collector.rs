@@ -1,10 +1,12 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use crate::config::*;
 use crate::plant::{PlantBiology, Genome, GeneticLineage};
 use crate::world::VoxelWorld;
+use crate::simulation::SimulationClock;
 
 /// Snapshot of simulation statistics at a point in time
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StatsSnapshot {
     pub timestamp: f32,
     pub population: usize,
@@ -16,6 +18,8 @@ pub struct StatsSnapshot {
     pub avg_growth_rate: f32,
     pub avg_height_gene: f32,
     pub avg_photosynthesis: f32,
+    pub avg_cold_tolerance: f32,
+    pub avg_leaf_drop: f32,
     pub total_biomass: u32,
 }
 
@@ -24,13 +28,22 @@ pub struct StatsSnapshot {
 pub struct StatisticsHistory {
     pub snapshots: Vec<StatsSnapshot>,
     pub update_timer: Timer,
+    pub collected: u64, // Total snapshots collected, including ones dropped from history
 }
 
 impl Default for StatisticsHistory {
     fn default() -> Self {
+        Self::new(STATS_UPDATE_INTERVAL)
+    }
+}
+
+impl StatisticsHistory {
+    /// Create an empty history sampled every `update_interval` seconds
+    pub fn new(update_interval: f32) -> Self {
         Self {
             snapshots: Vec::new(),
-            update_timer: Timer::from_seconds(STATS_UPDATE_INTERVAL, TimerMode::Repeating),
+            update_timer: Timer::from_seconds(update_interval, TimerMode::Repeating),
+            collected: 0,
         }
     }
 }
@@ -39,6 +52,8 @@ impl Default for StatisticsHistory {
 pub fn collect_statistics_system(
     mut stats: ResMut<StatisticsHistory>,
     plants: Query<(&PlantBiology, &Genome, &GeneticLineage)>,
+    config: Res<SimConfig>,
+    clock: Res<SimulationClock>,
     time: Res<Time>,
 ) {
     stats.update_timer.tick(time.delta());
@@ -60,6 +75,8 @@ pub fn collect_statistics_system(
     let mut total_growth_rate = 0.0;
     let mut total_height_gene = 0.0;
     let mut total_photosynthesis = 0.0;
+    let mut total_cold_tolerance = 0.0;
+    let mut total_leaf_drop = 0.0;
     let mut genomes: Vec<&Genome> = Vec::new();
 
     for (biology, genome, _) in plants.iter() {
@@ -73,6 +90,8 @@ pub fn collect_statistics_system(
         total_growth_rate += genome.growth_rate.value;
         total_height_gene += genome.max_height.value;
         total_photosynthesis += genome.photosynthesis_efficiency.value;
+        total_cold_tolerance += genome.cold_tolerance.value;
+        total_leaf_drop += genome.leaf_drop.value;
         genomes.push(genome);
     }
 
@@ -85,7 +104,7 @@ pub fn collect_statistics_system(
     let species_count = estimate_species_count(&genomes);
 
     let snapshot = StatsSnapshot {
-        timestamp: time.elapsed_secs(),
+        timestamp: clock.elapsed_secs(&time),
         population: plant_count,
         species_count,
         avg_energy: total_energy / count,
@@ -95,13 +114,16 @@ pub fn collect_statistics_system(
         avg_growth_rate: total_growth_rate / count,
         avg_height_gene: total_height_gene / count,
         avg_photosynthesis: total_photosynthesis / count,
+        avg_cold_tolerance: total_cold_tolerance / count,
+        avg_leaf_drop: total_leaf_drop / count,
         total_biomass: total_mass,
     };
 
     stats.snapshots.push(snapshot);
+    stats.collected += 1;
 
     // Keep only recent history
-    if stats.snapshots.len() > STATS_HISTORY_SIZE {
+    if stats.snapshots.len() > config.stats_history_size {
         stats.snapshots.remove(0);
     }
 }
@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::config::SimConfig;
+use super::collector::{StatisticsHistory, StatsSnapshot};
+
+/// Column names of the CSV export, in the order rows are written
+pub const CSV_HEADER: &str = "timestamp,population,species_count,avg_energy,avg_age,avg_mass,\
+genetic_diversity,avg_growth_rate,avg_height_gene,avg_photosynthesis,avg_cold_tolerance,avg_leaf_drop,total_biomass";
+
+/// Key that starts and stops exporting in the GUI
+pub const EXPORT_KEY: KeyCode = KeyCode::KeyE;
+
+/// Output format for exported statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+}
+
+impl ExportFormat {
+    /// Pick a format from a file extension (`.jsonl`/`.json` for JSON lines, CSV otherwise)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("jsonl") | Some("json") => ExportFormat::JsonLines,
+            _ => ExportFormat::Csv,
+        }
+    }
+}
+
+/// Resource that streams each collected `StatsSnapshot` to a file
+/// The file starts with run metadata: comment lines for CSV, a `metadata` object for JSON lines
+#[derive(Resource)]
+pub struct StatsExporter {
+    path: PathBuf,
+    format: ExportFormat,
+    writer: BufWriter<File>,
+    exported: u64, // Value of `StatisticsHistory::collected` at the last write
+}
+
+impl StatsExporter {
+    /// Create the file and write the run metadata and header
+    pub fn create(path: &Path, seed: u64, config: &SimConfig) -> Result<Self, String> {
+        let format = ExportFormat::from_path(path);
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create statistics file {}: {}", path.display(), e))?;
+
+        let mut exporter = Self {
+            path: path.to_path_buf(),
+            format,
+            writer: BufWriter::new(file),
+            exported: 0,
+        };
+        exporter
+            .write_metadata(seed, config)
+            .map_err(|e| format!("Failed to write statistics file {}: {}", path.display(), e))?;
+
+        Ok(exporter)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write_metadata(&mut self, seed: u64, config: &SimConfig) -> std::io::Result<()> {
+        match self.format {
+            ExportFormat::Csv => {
+                writeln!(self.writer, "# {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))?;
+                writeln!(self.writer, "# seed = {}", seed)?;
+                let config_toml = toml::to_string(config).map_err(std::io::Error::other)?;
+                for line in config_toml.lines() {
+                    writeln!(self.writer, "# {}", line)?;
+                }
+                writeln!(self.writer, "{}", CSV_HEADER)?;
+            }
+            ExportFormat::JsonLines => {
+                let metadata = serde_json::json!({
+                    "metadata": {
+                        "program": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "seed": seed,
+                        "config": config,
+                    }
+                });
+                writeln!(self.writer, "{}", metadata)?;
+            }
+        }
+
+        self.writer.flush()
+    }
+
+    /// Append one snapshot
+    pub fn write(&mut self, s: &StatsSnapshot) -> std::io::Result<()> {
+        match self.format {
+            ExportFormat::Csv => writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                s.timestamp,
+                s.population,
+                s.species_count,
+                s.avg_energy,
+                s.avg_age,
+                s.avg_mass,
+                s.genetic_diversity,
+                s.avg_growth_rate,
+                s.avg_height_gene,
+                s.avg_photosynthesis,
+                s.avg_cold_tolerance,
+                s.avg_leaf_drop,
+                s.total_biomass
+            )?,
+            ExportFormat::JsonLines => {
+                let line = serde_json::to_string(s).map_err(std::io::Error::other)?;
+                writeln!(self.writer, "{}", line)?;
+            }
+        }
+
+        // Flush every row so the file can be read while the run continues
+        self.writer.flush()
+    }
+
+    /// Write the whole current history, e.g. when exporting starts mid-run
+    pub fn write_history(&mut self, stats: &StatisticsHistory) -> std::io::Result<()> {
+        for snapshot in &stats.snapshots {
+            self.write(snapshot)?;
+        }
+        self.exported = stats.collected;
+        Ok(())
+    }
+}
+
+/// System to append newly collected snapshots to the export file
+pub fn export_statistics_system(
+    mut commands: Commands,
+    stats: Res<StatisticsHistory>,
+    mut exporter: ResMut<StatsExporter>,
+) {
+    if stats.collected == exporter.exported {
+        return;
+    }
+
+    // At most one snapshot is collected per tick, but catch up if several were
+    let new_count = ((stats.collected - exporter.exported) as usize).min(stats.snapshots.len());
+    let start = stats.snapshots.len() - new_count;
+
+    for snapshot in &stats.snapshots[start..] {
+        if let Err(e) = exporter.write(snapshot) {
+            eprintln!("Failed to write statistics to {}: {}", exporter.path().display(), e);
+            commands.remove_resource::<StatsExporter>();
+            return;
+        }
+    }
+
+    exporter.exported = stats.collected;
+}
+
+/// System to start or stop exporting statistics with a hotkey
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_hotkey_system(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    exporter: Option<Res<StatsExporter>>,
+    stats: Res<StatisticsHistory>,
+    sim_rng: Res<crate::rng::SimulationRng>,
+    config: Res<SimConfig>,
+) {
+    if !keyboard.just_pressed(EXPORT_KEY) {
+        return;
+    }
+
+    if let Some(exporter) = exporter {
+        println!("Stopped exporting statistics to {}", exporter.path().display());
+        commands.remove_resource::<StatsExporter>();
+        return;
+    }
+
+    // Named by seed and snapshot count, so repeated exports of a run don't overwrite each other
+    let path = PathBuf::from(format!("stats-{}-{}.csv", sim_rng.seed(), stats.collected));
+
+    match StatsExporter::create(&path, sim_rng.seed(), &config) {
+        Ok(mut exporter) => match exporter.write_history(&stats) {
+            Ok(()) => {
+                println!("Exporting statistics to {}", path.display());
+                commands.insert_resource(exporter);
+            }
+            Err(e) => eprintln!("Failed to write statistics to {}: {}", path.display(), e),
+        },
+        Err(e) => eprintln!("{}", e),
+    }
+}
@@ -1,6 +1,32 @@
 use bevy::prelude::*;
 use super::collector::StatisticsHistory;
-use crate::world::{YearCycle, get_season_name};
+use crate::config::SimConfig;
+use crate::world::{DayNightCycle, YearCycle, Weather, get_base_temperature, get_season_name};
+use crate::simulation::{SimulationClock, SimulationState};
+use crate::rendering::ColorSettings;
+use super::export::StatsExporter;
+
+/// Plugin for the on-screen statistics and controls panels
+pub struct StatsUiPlugin;
+
+impl Plugin for StatsUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GraphsVisibility::default())
+            .insert_resource(UIState::default())
+            .add_systems(Startup, setup_stats_ui)
+            .add_systems(Update, (
+                update_stats_display_system,
+                ui_toggle_button_system,
+                ui_keyboard_toggle_system,
+                update_panel_visibility_system,
+                time_control_button_system,
+            ));
+
+        // The web build has no file system to export to
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, super::export::export_hotkey_system);
+    }
+}
 
 /// Resource to control graph visibility
 #[derive(Resource, Default)]
@@ -38,6 +64,26 @@ pub struct ControlsPanel;
 #[derive(Component)]
 pub struct UIToggleButton;
 
+/// Time control actions available as buttons in the stats panel
+#[derive(Component, Clone, Copy)]
+pub enum TimeControlButton {
+    Slower,
+    Faster,
+    Step,
+    NextSeason,
+}
+
+impl TimeControlButton {
+    fn label(&self) -> &'static str {
+        match self {
+            TimeControlButton::Slower => "Slower",
+            TimeControlButton::Faster => "Faster",
+            TimeControlButton::Step => "Step",
+            TimeControlButton::NextSeason => "Next Season",
+        }
+    }
+}
+
 /// Setup the stats display UI
 pub fn setup_stats_ui(mut commands: Commands) {
     // Create a root node for the stats panel
@@ -64,6 +110,43 @@ pub fn setup_stats_ui(mut commands: Commands) {
                 TextColor(Color::WHITE),
                 StatsText,
             ));
+
+            // Time control buttons
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(5.0),
+                    margin: UiRect::top(Val::Px(10.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for action in [
+                        TimeControlButton::Slower,
+                        TimeControlButton::Faster,
+                        TimeControlButton::Step,
+                        TimeControlButton::NextSeason,
+                    ] {
+                        row.spawn((
+                            Node {
+                                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.9)),
+                            Button,
+                            action,
+                        ))
+                        .with_children(|button| {
+                            button.spawn((
+                                Text::new(action.label()),
+                                TextFont {
+                                    font_size: 14.0,
+                                    ..default()
+                                },
+                                TextColor(Color::WHITE),
+                            ));
+                        });
+                    }
+                });
         });
 
     // Create controls panel
@@ -87,8 +170,16 @@ pub fn setup_stats_ui(mut commands: Commands) {
                     WASD/Arrows: Pan camera\n\
                     Right Mouse: Rotate camera\n\
                     Mouse Wheel: Zoom\n\
+                    Left Click: Inspect plant\n\
                     Space/Shift: Move up/down\n\
                     P: Pause/Resume\n\
+                    [ / ]: Slower/Faster\n\
+                    Period: Step one tick (paused)\n\
+                    N: Run until next season\n\
+                    F5/F9: Quick save/load\n\
+                    E: Start/stop stats export\n\
+                    C: Cycle colour mode\n\
+                    G: Colour by gene (again for next)\n\
                     H: Toggle UI\n\
                     ESC: Quit\n\
                     \n\
@@ -138,19 +229,45 @@ pub fn setup_stats_ui(mut commands: Commands) {
 pub fn update_stats_display_system(
     stats: Res<StatisticsHistory>,
     year_cycle: Res<YearCycle>,
+    day_night: Res<DayNightCycle>,
+    weather: Res<Weather>,
+    config: Res<SimConfig>,
+    sim_state: Res<SimulationState>,
+    exporter: Option<Res<StatsExporter>>,
+    color_settings: Option<Res<ColorSettings>>,
     mut query: Query<&mut Text, With<StatsText>>,
+    clock: Res<SimulationClock>,
     time: Res<Time>,
 ) {
     if let Some(latest) = stats.snapshots.last() {
         let season = get_season_name(&year_cycle);
         let year_progress = (year_cycle.time_of_year * 100.0) as u32;
+        let weather_name = if weather.raining { "Raining" } else { "Dry" };
+        let temperature = get_base_temperature(&config, &day_night, &year_cycle);
+        let run_state = if sim_state.paused {
+            " (Paused)"
+        } else if sim_state.run_until_season.is_some() {
+            " (Until next season)"
+        } else {
+            ""
+        };
+        let export_status = match &exporter {
+            Some(exporter) => format!("\nExporting to {}", exporter.path().display()),
+            None => String::new(),
+        };
+        let color_mode = match &color_settings {
+            Some(settings) => format!("\nColours: {}", settings.label()),
+            None => String::new(),
+        };
 
         for mut text in query.iter_mut() {
             **text = format!(
                 "Plant Evolution Simulator\n\
                 \n\
                 Time: {:.1}s\n\
+                Speed: {}x{}\n\
                 Season: {} ({}%)\n\
+                Weather: {}, {:.1}°C\n\
                 Population: {}\n\
                 Species: {}\n\
                 \n\
@@ -164,11 +281,17 @@ pub fn update_stats_display_system(
                 Growth Rate: {:.2}\n\
                 Max Height: {:.2}\n\
                 Photosynthesis: {:.2}\n\
+                Cold Tolerance: {:.2}\n\
+                Leaf Drop: {:.2}\n\
                 \n\
-                Total Biomass: {} voxels",
-                time.elapsed_secs(),
+                Total Biomass: {} voxels{}{}",
+                clock.elapsed_secs(&time),
+                sim_state.time_scale,
+                run_state,
                 season,
                 year_progress,
+                weather_name,
+                temperature,
                 latest.population,
                 latest.species_count,
                 latest.avg_energy,
@@ -178,7 +301,11 @@ pub fn update_stats_display_system(
                 latest.avg_growth_rate,
                 latest.avg_height_gene,
                 latest.avg_photosynthesis,
-                latest.total_biomass
+                latest.avg_cold_tolerance,
+                latest.avg_leaf_drop,
+                latest.total_biomass,
+                color_mode,
+                export_status
             );
         }
     }
@@ -208,6 +335,38 @@ pub fn ui_toggle_button_system(
     }
 }
 
+/// Handle time control button clicks
+pub fn time_control_button_system(
+    mut interaction_query: Query<
+        (&Interaction, &TimeControlButton, &mut BackgroundColor),
+        Changed<Interaction>
+    >,
+    mut sim_state: ResMut<SimulationState>,
+    year_cycle: Res<YearCycle>,
+) {
+    for (interaction, action, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                match action {
+                    TimeControlButton::Slower => sim_state.slow_down(),
+                    TimeControlButton::Faster => sim_state.speed_up(),
+                    TimeControlButton::Step => sim_state.step(),
+                    TimeControlButton::NextSeason => {
+                        sim_state.run_until_next_season(get_season_name(&year_cycle))
+                    }
+                }
+                *color = BackgroundColor(Color::srgba(0.4, 0.4, 0.4, 0.9));
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(Color::srgba(0.3, 0.3, 0.3, 0.9));
+            }
+            Interaction::None => {
+                *color = BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.9));
+            }
+        }
+    }
+}
+
 /// Handle keyboard toggle
 pub fn ui_keyboard_toggle_system(
     keyboard: Res<ButtonInput<KeyCode>>,
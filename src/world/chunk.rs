@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use crate::config::CHUNK_SIZE;
+use super::voxel::{Voxel, VoxelPos};
+
+/// Number of voxels in a chunk
+pub const CHUNK_VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+
+/// Coordinate of a chunk in the chunk grid (voxel coordinate divided by `CHUNK_SIZE`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl ChunkPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// The chunk containing a voxel
+    pub fn containing(pos: &VoxelPos) -> Self {
+        let size = CHUNK_SIZE as i32;
+        Self::new(pos.x.div_euclid(size), pos.y.div_euclid(size), pos.z.div_euclid(size))
+    }
+
+    /// Position of the chunk's lowest corner voxel
+    pub fn origin(&self) -> VoxelPos {
+        let size = CHUNK_SIZE as i32;
+        VoxelPos::new(self.x * size, self.y * size, self.z * size)
+    }
+}
+
+/// A `CHUNK_SIZE`³ block of voxels, the unit of storage and remeshing
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Chunk {
+    pub(super) voxels: Vec<Voxel>, // Indexed by `Chunk::local_index`
+    #[serde(skip, default = "needs_mesh")]
+    pub(super) dirty: bool, // Set when a voxel type changes, cleared when the chunk is remeshed
+}
+
+/// New and loaded chunks have never been meshed
+fn needs_mesh() -> bool {
+    true
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self {
+            voxels: vec![Voxel::default(); CHUNK_VOLUME],
+            dirty: needs_mesh(),
+        }
+    }
+
+    /// Index of a voxel within a chunk from its local coordinates (each below `CHUNK_SIZE`)
+    pub fn local_index(x: usize, y: usize, z: usize) -> usize {
+        x + z * CHUNK_SIZE + y * CHUNK_SIZE * CHUNK_SIZE
+    }
+
+    /// Local (x, y, z) coordinates of a voxel index within a chunk
+    pub fn local_coords(index: usize) -> (usize, usize, usize) {
+        (
+            index % CHUNK_SIZE,
+            index / (CHUNK_SIZE * CHUNK_SIZE),
+            (index / CHUNK_SIZE) % CHUNK_SIZE,
+        )
+    }
+}
+
+impl Default for Chunk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
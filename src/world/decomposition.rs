@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use crate::config::*;
+use super::voxel::{VoxelWorld, VoxelPos, VoxelType};
+
+/// Turn the voxels of a dead plant into litter holding `nutrients` in total
+/// Underground voxels (dead roots) decay in place; voxels above ground fall until they land on something solid
+pub fn deposit_litter(world: &mut VoxelWorld, positions: &[VoxelPos], nutrients: f32) {
+    if positions.is_empty() {
+        return;
+    }
+    let nutrients_per_voxel = nutrients / positions.len() as f32;
+
+    // Clear the standing plant first, so falling litter passes through the space it occupied
+    let mut falling = Vec::new();
+    for pos in positions {
+        if pos.y < world.ground_level(pos.x, pos.z) {
+            world.set_voxel_type(pos, VoxelType::Litter);
+            if let Some(voxel) = world.get_mut(pos) {
+                voxel.environment.nutrients = nutrients_per_voxel;
+            }
+        } else {
+            world.set_voxel_type(pos, VoxelType::Air);
+            falling.push(*pos);
+        }
+    }
+
+    // Drop the lowest voxels first so litter piles up
+    falling.sort_by_key(|pos| pos.y);
+    for pos in falling {
+        let mut landing = pos;
+        let landed = loop {
+            let below = VoxelPos::new(landing.x, landing.y - 1, landing.z);
+            match world.get(&below) {
+                Some(voxel) if voxel.voxel_type.is_air() => landing = below,
+                Some(_) => break true,
+                // Fell out of the world
+                None => break false,
+            }
+        };
+
+        if !landed {
+            continue;
+        }
+        world.set_voxel_type(&landing, VoxelType::Litter);
+        if let Some(voxel) = world.get_mut(&landing) {
+            voxel.environment.nutrients = nutrients_per_voxel;
+        }
+    }
+}
+
+/// System to decompose litter, releasing its nutrients into the soil below
+/// Nutrients the soil has no room for are lost; fully decomposed litter becomes soil underground or air above
+/// Only the world's list of litter voxels is visited, not the whole grid
+pub fn decompose_litter_system(mut world: ResMut<VoxelWorld>, config: Res<SimConfig>) {
+    let litter = std::mem::take(&mut world.litter);
+    let mut remaining = Vec::with_capacity(litter.len());
+
+    for pos in litter {
+        let (released, decomposed) = match world.get_mut(&pos) {
+            Some(voxel) if voxel.voxel_type == VoxelType::Litter => {
+                let released = voxel.environment.nutrients * config.litter_decay_rate;
+                voxel.environment.nutrients -= released;
+                (released, voxel.environment.nutrients < config.litter_min_nutrients)
+            }
+            // No longer litter, so nothing left to decompose
+            _ => continue,
+        };
+
+        let ground = world.ground_level(pos.x, pos.z);
+        if decomposed {
+            let remains = if pos.y < ground { VoxelType::Soil } else { VoxelType::Air };
+            world.set_voxel_type(&pos, remains);
+        } else {
+            remaining.push(pos);
+        }
+
+        // Feed the first soil voxel below the litter; the column above the terrain holds no soil
+        let soil = (0..pos.y.min(ground))
+            .rev()
+            .map(|below| VoxelPos::new(pos.x, below, pos.z))
+            .find(|below| matches!(world.get(below).map(|v| v.voxel_type), Some(VoxelType::Soil)));
+        if let Some(voxel) = soil.and_then(|soil| world.get_mut(&soil)) {
+            let env = &mut voxel.environment;
+            env.nutrients = (env.nutrients + released).min(env.nutrient_capacity(&config));
+        }
+    }
+
+    world.litter.extend(remaining);
+}
@@ -1,60 +1,23 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use crate::config::*;
-use super::voxel::{VoxelWorld, VoxelPos, VoxelType};
+use super::voxel::{VoxelWorld, VoxelType};
 
-/// System to update light levels in the world
-pub fn update_light_system(mut world: ResMut<VoxelWorld>) {
-    // Calculate light levels from top to bottom
-    for x in 0..world.width() {
-        for z in 0..world.depth() {
-            let mut light = SUNLIGHT_MAX;
+/// System to regenerate soil nutrients (water moves through `hydrology_system`)
+pub fn regenerate_resources_system(mut world: ResMut<VoxelWorld>, config: Res<SimConfig>) {
+    for (_, voxel) in world.ground_voxels_mut() {
+        if matches!(voxel.voxel_type, VoxelType::Soil) {
+            let env = &mut voxel.environment;
 
-            // Propagate light downward
-            for y in (0..world.height()).rev() {
-                let pos = VoxelPos::new(x as i32, y as i32, z as i32);
-
-                if let Some(voxel) = world.get_mut(&pos) {
-                    voxel.environment.light_level = light;
-
-                    // Reduce light based on material type
-                    match voxel.voxel_type {
-                        VoxelType::PlantMaterial { .. } => {
-                            // Plants block 40% of light (canopy shading)
-                            light *= 0.6;
-                        }
-                        VoxelType::Soil => {
-                            // Soil blocks almost all light
-                            light *= 0.1;
-                        }
-                        VoxelType::Air => {
-                            // Air doesn't block light
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
-
-/// System to regenerate soil nutrients and water
-pub fn regenerate_resources_system(mut world: ResMut<VoxelWorld>) {
-    for pos in world.iter_positions().collect::<Vec<_>>() {
-        if let Some(voxel) = world.get_mut(&pos) {
-            if matches!(voxel.voxel_type, VoxelType::Soil) {
-                // Regenerate nutrients slowly
-                voxel.environment.nutrients = (voxel.environment.nutrients + NUTRIENT_REGEN_RATE)
-                    .min(SOIL_NUTRIENT_MAX);
-
-                // Regenerate water faster
-                voxel.environment.water = (voxel.environment.water + WATER_REGEN_RATE)
-                    .min(SOIL_WATER_MAX);
-            }
+            // Regenerate nutrients slowly, faster in fertile patches
+            env.nutrients = (env.nutrients + config.nutrient_regen_rate * env.fertility)
+                .min(env.nutrient_capacity(&config));
         }
     }
 }
 
 /// Resource to track day/night cycle
-#[derive(Resource)]
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct DayNightCycle {
     pub time_of_day: f32, // 0.0 to 1.0
     pub day_length: f32,  // seconds
@@ -64,7 +27,7 @@ impl Default for DayNightCycle {
     fn default() -> Self {
         Self {
             time_of_day: 0.5, // Start at noon
-            day_length: 60.0, // 60 second days
+            day_length: DAY_LENGTH,
         }
     }
 }
@@ -83,7 +46,7 @@ pub fn get_sunlight_multiplier(cycle: &DayNightCycle) -> f32 {
 }
 
 /// Resource to track yearly seasonal cycle
-#[derive(Resource)]
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct YearCycle {
     pub time_of_year: f32, // 0.0 to 1.0
     pub year_length: f32,  // seconds
@@ -93,7 +56,7 @@ impl Default for YearCycle {
     fn default() -> Self {
         Self {
             time_of_year: 0.0, // Start at spring
-            year_length: 300.0, // 5 minute years (300 seconds)
+            year_length: YEAR_LENGTH,
         }
     }
 }
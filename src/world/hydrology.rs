@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::config::*;
+use crate::rng::SimulationRng;
+use super::environment::{DayNightCycle, YearCycle, get_sunlight_multiplier, get_seasonal_multiplier};
+use super::voxel::VoxelWorld;
+
+/// Resource to track rain events
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct Weather {
+    pub raining: bool,
+    pub rain_remaining: f32, // Seconds left in the current rain event
+}
+
+/// System to start and stop rain events
+pub fn update_weather_system(
+    mut weather: ResMut<Weather>,
+    mut sim_rng: ResMut<SimulationRng>,
+    config: Res<SimConfig>,
+    time: Res<Time>,
+) {
+    let rng = &mut **sim_rng;
+
+    if weather.raining {
+        weather.rain_remaining -= time.delta_secs();
+        if weather.rain_remaining <= 0.0 {
+            weather.raining = false;
+            weather.rain_remaining = 0.0;
+        }
+    } else if rng.random::<f32>() < config.rain_chance * time.delta_secs() {
+        // Rain lasts between half and one and a half times the average duration
+        weather.raining = true;
+        weather.rain_remaining = config.rain_duration * rng.random_range(0.5..1.5);
+    }
+}
+
+/// Working buffers for `hydrology_system`, kept between ticks to avoid reallocating them
+/// Holds one entry per ground voxel, with each column's voxels stored together from the bottom up
+#[derive(Default)]
+pub struct HydrologyBuffers {
+    columns: Vec<(usize, usize)>, // Start of each column's voxels and its ground level, indexed by `x + z * width`
+    water: Vec<f32>,
+    capacity: Vec<f32>, // 0 for ground voxels that hold no water
+    delta: Vec<f32>,
+}
+
+impl HydrologyBuffers {
+    /// Lay the buffers out for the world's ground voxels and clear them
+    fn reset(&mut self, world: &VoxelWorld) {
+        self.columns.clear();
+        let mut total = 0;
+        for z in 0..world.depth() as i32 {
+            for x in 0..world.width() as i32 {
+                let ground = world.ground_level(x, z).max(0) as usize;
+                self.columns.push((total, ground));
+                total += ground;
+            }
+        }
+
+        for buffer in [&mut self.water, &mut self.capacity, &mut self.delta] {
+            buffer.clear();
+            buffer.resize(total, 0.0);
+        }
+    }
+
+    /// Buffer index of a ground voxel, or `None` above the ground
+    fn index(&self, width: usize, x: usize, y: usize, z: usize) -> Option<usize> {
+        let (start, ground) = self.columns[x + z * width];
+        (y < ground).then_some(start + y)
+    }
+}
+
+/// System to move water through the ground
+/// Rain soaks into the top ground voxel, water percolates downward and diffuses sideways,
+/// evaporates from sunlit voxels, and ground below the water table stays saturated
+pub fn hydrology_system(
+    mut world: ResMut<VoxelWorld>,
+    mut buffers: Local<HydrologyBuffers>,
+    weather: Res<Weather>,
+    day_night: Res<DayNightCycle>,
+    year_cycle: Res<YearCycle>,
+    config: Res<SimConfig>,
+) {
+    let width = world.width();
+    let depth = world.depth();
+    let sun = get_sunlight_multiplier(&day_night) * get_seasonal_multiplier(&year_cycle);
+
+    buffers.reset(&world);
+    let buffers = &mut *buffers;
+
+    // Water and capacity of every ground voxel
+    for (pos, voxel) in world.ground_voxels_mut() {
+        let (x, y, z) = (pos.x as usize, pos.y as usize, pos.z as usize);
+        let (start, ground) = buffers.columns[x + z * width];
+        let water_table = ground as i32 - config.water_table_depth;
+
+        let env = &mut voxel.environment;
+        let max_water = env.water_capacity(&config);
+
+        if pos.y < water_table {
+            // Saturated ground below the water table
+            env.water = max_water;
+        } else {
+            // Evaporation scales with the light reaching the voxel
+            let light = env.light_level / config.sunlight_max * sun;
+            env.water = (env.water - config.evaporation_rate * light).max(0.0);
+
+            // Rain soaks into the top ground voxel, more in moist patches
+            if weather.raining && y + 1 == ground {
+                env.water = (env.water + config.rain_rate * env.moisture).min(max_water);
+            }
+        }
+
+        buffers.water[start + y] = env.water;
+        buffers.capacity[start + y] = max_water;
+    }
+
+    // Percolation: a fraction of each voxel's water sinks into the voxel below, if it has room
+    let (water, capacity) = (&mut buffers.water, &buffers.capacity);
+    for &(start, ground) in &buffers.columns {
+        for y in (1..ground).rev() {
+            let idx = start + y;
+            let below = idx - 1;
+            if capacity[idx] <= 0.0 || capacity[below] <= 0.0 {
+                continue;
+            }
+
+            let flow = (water[idx] * config.percolation_rate)
+                .min(capacity[below] - water[below])
+                .max(0.0);
+            water[idx] -= flow;
+            water[below] += flow;
+        }
+    }
+
+    // Lateral diffusion: water flows from wetter to drier neighbours at the same height
+    for z in 0..depth {
+        for x in 0..width {
+            let (start, ground) = buffers.columns[x + z * width];
+            for y in 0..ground {
+                let idx = start + y;
+                if buffers.capacity[idx] <= 0.0 {
+                    continue;
+                }
+
+                let neighbors = [
+                    (x + 1 < width).then(|| buffers.index(width, x + 1, y, z)).flatten(),
+                    (z + 1 < depth).then(|| buffers.index(width, x, y, z + 1)).flatten(),
+                ];
+                for neighbor in neighbors.into_iter().flatten() {
+                    if buffers.capacity[neighbor] <= 0.0 {
+                        continue;
+                    }
+
+                    let flow = (buffers.water[idx] - buffers.water[neighbor]) * config.lateral_diffusion_rate;
+                    buffers.delta[idx] -= flow;
+                    buffers.delta[neighbor] += flow;
+                }
+            }
+        }
+    }
+
+    // Write the results back to the world
+    for (pos, voxel) in world.ground_voxels_mut() {
+        let (start, _) = buffers.columns[pos.x as usize + pos.z as usize * width];
+        let idx = start + pos.y as usize;
+        voxel.environment.water = (buffers.water[idx] + buffers.delta[idx]).clamp(0.0, buffers.capacity[idx]);
+    }
+}
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use crate::config::*;
+use super::environment::{DayNightCycle, YearCycle};
+use super::voxel::{VoxelWorld, VoxelPos};
+
+/// Position of the sun in the sky
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunPosition {
+    pub elevation: f32, // Radians above the horizon (negative at night)
+    pub azimuth: f32,   // Radians from +X toward +Z: rises at +X, highest toward +Z, sets at -X
+}
+
+impl SunPosition {
+    /// Horizontal voxels travelled per voxel of height when following a ray toward the sun
+    /// `None` while the sun is below the horizon
+    pub fn ray_slope(&self) -> Option<Vec2> {
+        if self.elevation <= 0.0 {
+            return None;
+        }
+        let run = (1.0 / self.elevation.tan()).min(MAX_SUN_RAY_SLOPE);
+        Some(Vec2::new(self.azimuth.cos(), self.azimuth.sin()) * run)
+    }
+
+    /// Round both angles to a multiple of `step_degrees`
+    pub fn snapped(&self, step_degrees: f32) -> Self {
+        let step = step_degrees.to_radians();
+        Self {
+            elevation: (self.elevation / step).round() * step,
+            azimuth: (self.azimuth / step).round() * step,
+        }
+    }
+}
+
+/// Get the sun position for the time of day and season
+/// The sun follows the `get_sunlight_multiplier` curve and climbs highest in mid-summer
+pub fn get_sun_position(day_night: &DayNightCycle, year_cycle: &YearCycle) -> SunPosition {
+    use std::f32::consts::{PI, TAU};
+
+    // Mid-summer (0.375) has the highest noon sun, mid-winter (0.875) the lowest
+    let season = ((year_cycle.time_of_year - 0.375) * TAU).cos() * 0.5 + 0.5;
+    let max_elevation = (SUN_WINTER_ELEVATION + (SUN_SUMMER_ELEVATION - SUN_WINTER_ELEVATION) * season)
+        .to_radians();
+
+    SunPosition {
+        elevation: (day_night.time_of_day * TAU).sin() * max_elevation,
+        azimuth: (day_night.time_of_day * 2.0).fract() * PI,
+    }
+}
+
+/// Horizontal offset of a sun ray at each layer, relative to where it enters the top of the world
+/// A voxel at (x, y, z) lies on the ray entering at (x + offset.x, z + offset.y)
+/// `None` at night, when there are no direct rays
+fn ray_offsets(sun: &SunPosition, height: usize) -> Option<Vec<IVec2>> {
+    let slope = sun.ray_slope()?;
+    Some(
+        (0..height)
+            .map(|y| {
+                let steps = (height - y) as f32;
+                IVec2::new((slope.x * steps).round() as i32, (slope.y * steps).round() as i32)
+            })
+            .collect(),
+    )
+}
+
+/// Direct and diffuse light from the last update, kept so changed columns can be relit alone
+/// Indexed by `x + z * width + y * width * depth`
+#[derive(Default)]
+pub struct LightCache {
+    offsets: Option<Vec<IVec2>>,
+    direct: Vec<f32>,
+    diffuse: Vec<f32>,
+}
+
+/// System to update light levels in the world
+/// Light is a direct beam traced at the sun's angle plus diffuse skylight from straight above,
+/// so tall plants shade their neighbours on the side away from the sun
+/// The whole grid is relit only when the sun moves by `SUN_ANGLE_STEP` or the world is replaced;
+/// otherwise only columns whose voxels changed, and the rays passing through them, are relit
+pub fn update_light_system(
+    mut world: ResMut<VoxelWorld>,
+    day_night: Res<DayNightCycle>,
+    year_cycle: Res<YearCycle>,
+    config: Res<SimConfig>,
+    mut cache: Local<LightCache>,
+) {
+    let sun = get_sun_position(&day_night, &year_cycle).snapped(SUN_ANGLE_STEP);
+    let offsets = ray_offsets(&sun, world.height());
+    let voxel_count = world.width() * world.height() * world.depth();
+    let dirty = world.take_dirty_columns();
+
+    match dirty {
+        Some(columns) if cache.offsets == offsets && cache.direct.len() == voxel_count => {
+            relight_columns(&mut world, &config, &mut cache, &columns);
+        }
+        _ => {
+            cache.offsets = offsets;
+            relight_all(&mut world, &config, &mut cache);
+        }
+    }
+}
+
+/// Relight every voxel, sweeping layer by layer from the sky down
+fn relight_all(world: &mut VoxelWorld, config: &SimConfig, cache: &mut LightCache) {
+    let (width, height, depth) = (world.width(), world.height(), world.depth());
+    let direct_max = config.sunlight_max * (1.0 - config.diffuse_light_fraction);
+    let diffuse_max = config.sunlight_max * config.diffuse_light_fraction;
+
+    cache.direct = vec![0.0; width * height * depth];
+    cache.diffuse = vec![0.0; width * height * depth];
+
+    // Light leaving the layer above, indexed by `x + z * width`
+    let mut direct_above = vec![direct_max; width * depth];
+    let mut diffuse_above = vec![diffuse_max; width * depth];
+    let mut direct_layer = vec![0.0; width * depth];
+
+    for y in (0..height).rev() {
+        // Horizontal shift toward the sun between this layer and the one above
+        let shift = cache.offsets.as_ref().map(|offsets| {
+            let above = offsets.get(y + 1).copied().unwrap_or(IVec2::ZERO);
+            offsets[y] - above
+        });
+
+        for z in 0..depth {
+            for x in 0..width {
+                let column = x + z * width;
+                let idx = column + y * width * depth;
+
+                // Rays entering from outside the world are unobstructed
+                let direct = match shift {
+                    Some(shift) => {
+                        let source_x = x as i32 + shift.x;
+                        let source_z = z as i32 + shift.y;
+                        if source_x < 0 || source_z < 0 || source_x >= width as i32 || source_z >= depth as i32 {
+                            direct_max
+                        } else {
+                            direct_above[source_x as usize + source_z as usize * width]
+                        }
+                    }
+                    // No direct sun at night
+                    None => 0.0,
+                };
+                let diffuse = diffuse_above[column];
+                cache.direct[idx] = direct;
+                cache.diffuse[idx] = diffuse;
+
+                let pos = VoxelPos::new(x as i32, y as i32, z as i32);
+                if let Some(voxel) = world.get_mut(&pos) {
+                    voxel.environment.light_level = direct + diffuse;
+
+                    // Reduce light passing through based on material type
+                    let transmission = voxel.voxel_type.light_transmission();
+                    direct_layer[column] = direct * transmission;
+                    diffuse_above[column] = diffuse * transmission;
+                }
+            }
+        }
+
+        std::mem::swap(&mut direct_above, &mut direct_layer);
+    }
+}
+
+/// Relight only the given columns and every sun ray passing through them
+/// Gives the same result as `relight_all` for the same world and sun
+fn relight_columns(world: &mut VoxelWorld, config: &SimConfig, cache: &mut LightCache, columns: &[(i32, i32)]) {
+    if columns.is_empty() {
+        return;
+    }
+
+    let (width, height, depth) = (world.width(), world.height(), world.depth());
+    let direct_max = config.sunlight_max * (1.0 - config.diffuse_light_fraction);
+    let diffuse_max = config.sunlight_max * config.diffuse_light_fraction;
+    let index = |x: i32, y: usize, z: i32| x as usize + z as usize * width + y * width * depth;
+    let in_bounds = |x: i32, z: i32| x >= 0 && z >= 0 && (x as usize) < width && (z as usize) < depth;
+
+    // Diffuse skylight only falls straight down, so it only changes in the changed columns
+    for &(x, z) in columns {
+        let mut diffuse = diffuse_max;
+        for y in (0..height).rev() {
+            cache.diffuse[index(x, y, z)] = diffuse;
+            if let Some(voxel) = world.get(&VoxelPos::new(x, y as i32, z)) {
+                diffuse *= voxel.voxel_type.light_transmission();
+            }
+        }
+    }
+
+    // Direct rays crossing a changed column, identified by where they enter the top of the world
+    let mut rays: Vec<IVec2> = match &cache.offsets {
+        Some(offsets) => columns
+            .iter()
+            .flat_map(|&(x, z)| offsets.iter().map(move |offset| IVec2::new(x, z) + *offset))
+            .collect(),
+        None => Vec::new(),
+    };
+    rays.sort_by_key(|ray| (ray.x, ray.y));
+    rays.dedup();
+
+    if let Some(offsets) = &cache.offsets {
+        for ray in rays {
+            let mut direct = direct_max;
+            for y in (0..height).rev() {
+                let column = ray - offsets[y];
+                if !in_bounds(column.x, column.y) {
+                    // Outside the world the ray is unobstructed
+                    direct = direct_max;
+                    continue;
+                }
+
+                let idx = index(column.x, y, column.y);
+                cache.direct[idx] = direct;
+                if let Some(voxel) = world.get_mut(&VoxelPos::new(column.x, y as i32, column.y)) {
+                    voxel.environment.light_level = direct + cache.diffuse[idx];
+                    direct *= voxel.voxel_type.light_transmission();
+                }
+            }
+        }
+    }
+
+    // Every voxel of a changed column lies on a relit ray, but at night there are no rays
+    for &(x, z) in columns {
+        for y in 0..height {
+            let idx = index(x, y, z);
+            if let Some(voxel) = world.get_mut(&VoxelPos::new(x, y as i32, z)) {
+                voxel.environment.light_level = cache.direct[idx] + cache.diffuse[idx];
+            }
+        }
+    }
+}
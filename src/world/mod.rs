@@ -1,7 +1,18 @@
 pub mod voxel;
+pub mod chunk;
 pub mod environment;
+pub mod terrain;
+pub mod hydrology;
+pub mod decomposition;
+pub mod temperature;
+pub mod light;
 
-pub use voxel::{Voxel, VoxelType, VoxelPos, VoxelWorld, VoxelEnvironment};
-pub use environment::{DayNightCycle, YearCycle, update_light_system, regenerate_resources_system,
+pub use voxel::{Organ, Voxel, VoxelType, VoxelPos, VoxelWorld, VoxelEnvironment};
+pub use chunk::{Chunk, ChunkPos, CHUNK_VOLUME};
+pub use environment::{DayNightCycle, YearCycle, regenerate_resources_system,
                      update_day_night_system, update_year_cycle_system,
                      get_sunlight_multiplier, get_seasonal_multiplier, get_season_name};
+pub use light::{SunPosition, get_sun_position, update_light_system};
+pub use hydrology::{Weather, update_weather_system, hydrology_system};
+pub use decomposition::{deposit_litter, decompose_litter_system};
+pub use temperature::{TemperatureField, update_temperature_system, get_base_temperature};
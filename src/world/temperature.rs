@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use crate::config::*;
+use super::environment::{DayNightCycle, YearCycle};
+use super::voxel::VoxelWorld;
+
+/// Resource holding the air temperature (°C) of each voxel column, indexed by `x + z * width`
+#[derive(Resource)]
+pub struct TemperatureField {
+    values: Vec<f32>,
+    width: usize,
+    depth: usize,
+    mean: f32, // Configured mean temperature, for columns outside the world or not yet filled
+}
+
+impl Default for TemperatureField {
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            width: 0,
+            depth: 0,
+            mean: MEAN_TEMPERATURE,
+        }
+    }
+}
+
+impl TemperatureField {
+    /// Temperature of the column at (x, z), or the mean temperature outside the world
+    pub fn get(&self, x: i32, z: i32) -> f32 {
+        if x < 0 || z < 0 || x as usize >= self.width || z as usize >= self.depth {
+            return self.mean;
+        }
+        self.values
+            .get(x as usize + z as usize * self.width)
+            .copied()
+            .unwrap_or(self.mean)
+    }
+}
+
+/// Temperature at sea level for the current season and time of day
+/// Warmest in mid-summer afternoons, coldest on mid-winter nights
+pub fn get_base_temperature(config: &SimConfig, day_night: &DayNightCycle, year_cycle: &YearCycle) -> f32 {
+    use std::f32::consts::TAU;
+
+    // Mid-summer (0.375) is the peak of the year, mid-winter (0.875) the trough
+    let seasonal = ((year_cycle.time_of_year - 0.375) * TAU).cos() * config.seasonal_temperature_range;
+    // Follows the sunlight curve of `get_sunlight_multiplier`
+    let daily = (day_night.time_of_day * TAU).sin() * config.daily_temperature_range;
+
+    config.mean_temperature + seasonal + daily
+}
+
+/// System to update the temperature of every column
+/// Higher ground is colder, so frost reaches hilltops first
+pub fn update_temperature_system(
+    mut temperature: ResMut<TemperatureField>,
+    world: Res<VoxelWorld>,
+    day_night: Res<DayNightCycle>,
+    year_cycle: Res<YearCycle>,
+    config: Res<SimConfig>,
+) {
+    let base = get_base_temperature(&config, &day_night, &year_cycle);
+    let sea_level = world.height() as f32 * config.terrain_base_height;
+
+    temperature.width = world.width();
+    temperature.depth = world.depth();
+    temperature.mean = config.mean_temperature;
+    temperature.values.clear();
+    for z in 0..world.depth() as i32 {
+        for x in 0..world.width() as i32 {
+            let altitude = world.ground_level(x, z) as f32 - sea_level;
+            temperature.values.push(base - altitude * config.temperature_lapse_rate);
+        }
+    }
+}
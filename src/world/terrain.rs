@@ -0,0 +1,54 @@
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use crate::config::{SimConfig, MIN_SOIL_QUALITY};
+
+/// Generate a heightmap of soil column heights (in voxels), indexed by `x + z * width`
+/// Heights follow fractal Perlin noise around `terrain_base_height`, giving hills, valleys and slopes
+pub fn generate_heightmap(config: &SimConfig, seed: u32) -> Vec<usize> {
+    let noise = Fbm::<Perlin>::new(seed)
+        .set_octaves(config.terrain_octaves.max(1))
+        .set_frequency(config.terrain_frequency);
+
+    let base = config.world_height as f64 * config.terrain_base_height as f64;
+    // Leave at least one soil layer and two layers of air for growth
+    let max_height = config.world_height.saturating_sub(2).max(1);
+
+    let mut heights = Vec::with_capacity(config.world_width * config.world_depth);
+    for z in 0..config.world_depth {
+        for x in 0..config.world_width {
+            let n = noise.get([x as f64, z as f64]);
+            let height = base + n * config.terrain_amplitude as f64;
+            heights.push((height.round().max(1.0) as usize).min(max_height));
+        }
+    }
+
+    heights
+}
+
+/// Generate a soil quality field, indexed by `x + z * width` like the heightmap
+/// Values are multipliers around 1.0 (`1 ± variation`), so patches of rich and poor soil form
+pub fn generate_soil_field(config: &SimConfig, seed: u32, frequency: f64, variation: f32) -> Vec<f32> {
+    let noise = Fbm::<Perlin>::new(seed)
+        .set_octaves(2)
+        .set_frequency(frequency);
+
+    let mut field = Vec::with_capacity(config.world_width * config.world_depth);
+    for z in 0..config.world_depth {
+        for x in 0..config.world_width {
+            let n = noise.get([x as f64, z as f64]) as f32;
+            field.push((1.0 + n.clamp(-1.0, 1.0) * variation).max(MIN_SOIL_QUALITY));
+        }
+    }
+
+    field
+}
+
+/// Derive a noise seed from the simulation seed
+pub fn terrain_seed(seed: u64) -> u32 {
+    (seed ^ (seed >> 32)) as u32
+}
+
+/// Derive the fertility and moisture noise seeds from the terrain seed
+/// Offset so the fields don't line up with the hills
+pub fn soil_field_seeds(terrain_seed: u32) -> (u32, u32) {
+    (terrain_seed.wrapping_add(1), terrain_seed.wrapping_add(2))
+}
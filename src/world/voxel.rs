@@ -1,12 +1,27 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use crate::config::*;
+use super::chunk::{Chunk, ChunkPos};
+use super::terrain::{generate_heightmap, generate_soil_field, soil_field_seeds};
+
+/// Part of a plant that a plant voxel belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Organ {
+    Stem,
+    Leaf,
+    Root,
+    Flower, // Grown before the plant disperses seeds
+    Seed,   // A new plant's first voxel, which becomes a root when it germinates
+    Storage, // Underground tuber holding energy reserves
+}
 
 /// Represents the type of material in a voxel
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VoxelType {
     Air,
     Soil,
-    PlantMaterial { plant_id: u32, species_id: u32 },
+    PlantMaterial { plant_id: u32, species_id: u32, organ: Organ },
+    Litter, // Decomposing dead plant material; its nutrients are stored in the voxel's environment
 }
 
 impl VoxelType {
@@ -22,18 +37,53 @@ impl VoxelType {
         matches!(self, VoxelType::PlantMaterial { .. })
     }
 
+    /// The plant organ this voxel belongs to, if it is plant material
+    pub fn organ(&self) -> Option<Organ> {
+        match self {
+            VoxelType::PlantMaterial { organ, .. } => Some(*organ),
+            _ => None,
+        }
+    }
+
+    /// Fraction of light passing through this voxel
+    pub fn light_transmission(&self) -> f32 {
+        match self {
+            // Air doesn't block light
+            VoxelType::Air => 1.0,
+            // Soil blocks almost all light
+            VoxelType::Soil => 0.1,
+            // Leaves absorb most light for photosynthesis, forming the canopy shade
+            VoxelType::PlantMaterial { organ: Organ::Leaf, .. } => 0.4,
+            // Flowers and thin stems let more light past
+            VoxelType::PlantMaterial { organ: Organ::Stem | Organ::Flower, .. } => 0.7,
+            // Roots, seeds and tubers sit in the ground and block light like soil
+            VoxelType::PlantMaterial { organ: Organ::Root | Organ::Seed | Organ::Storage, .. } => 0.1,
+            // Litter lets a little light through gaps
+            VoxelType::Litter => 0.3,
+        }
+    }
+
     pub fn get_color(&self) -> Color {
         match self {
             VoxelType::Air => Color::srgba(0.0, 0.0, 0.0, 0.0),
             VoxelType::Soil => Color::srgb(0.4, 0.3, 0.2),
-            VoxelType::PlantMaterial { species_id, .. } => {
+            VoxelType::Litter => Color::srgb(0.3, 0.22, 0.1),
+            VoxelType::PlantMaterial { species_id, organ, .. } => {
                 // Generate distinct color for each species using simple hash
                 let hue = (*species_id as f32 * 137.5) % 360.0; // Golden angle for good distribution
                 let saturation = 0.6 + ((*species_id % 3) as f32 * 0.15); // 0.6-0.9
                 let lightness = 0.4 + ((*species_id % 5) as f32 * 0.1); // 0.4-0.8
 
-                // Convert HSL to RGB
-                Self::hsl_to_rgb(hue, saturation, lightness)
+                // Leaves show the species colour; other organs are shaded from it
+                match organ {
+                    Organ::Leaf => Self::hsl_to_rgb(hue, saturation, lightness),
+                    Organ::Stem => Self::hsl_to_rgb(hue, saturation * 0.4, lightness * 0.6),
+                    Organ::Root => Self::hsl_to_rgb(hue, 0.25, 0.75),
+                    // Flowers stand out in the complementary hue
+                    Organ::Flower => Self::hsl_to_rgb((hue + 180.0) % 360.0, 0.9, 0.65),
+                    Organ::Seed => Self::hsl_to_rgb(hue, 0.5, 0.25),
+                    Organ::Storage => Self::hsl_to_rgb((hue + 30.0) % 360.0, 0.45, 0.45),
+                }
             },
         }
     }
@@ -62,11 +112,25 @@ impl VoxelType {
 }
 
 /// Environmental data for each voxel
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct VoxelEnvironment {
     pub light_level: f32,
     pub nutrients: f32,
     pub water: f32,
+    pub fertility: f32, // Multiplier on nutrient capacity and regeneration
+    pub moisture: f32,  // Multiplier on water capacity and rainfall
+}
+
+impl VoxelEnvironment {
+    /// Most nutrients this voxel can hold
+    pub fn nutrient_capacity(&self, config: &SimConfig) -> f32 {
+        config.soil_nutrient_max * self.fertility
+    }
+
+    /// Most water this voxel can hold
+    pub fn water_capacity(&self, config: &SimConfig) -> f32 {
+        config.soil_water_max * self.moisture
+    }
 }
 
 impl Default for VoxelEnvironment {
@@ -75,12 +139,14 @@ impl Default for VoxelEnvironment {
             light_level: 0.0,
             nutrients: SOIL_NUTRIENT_MAX,
             water: SOIL_WATER_MAX,
+            fertility: 1.0,
+            moisture: 1.0,
         }
     }
 }
 
 /// Complete voxel data
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Voxel {
     pub voxel_type: VoxelType,
     pub environment: VoxelEnvironment,
@@ -96,7 +162,7 @@ impl Default for Voxel {
 }
 
 /// 3D coordinate in the world grid
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component, Serialize, Deserialize)]
 pub struct VoxelPos {
     pub x: i32,
     pub y: i32,
@@ -128,68 +194,226 @@ impl VoxelPos {
     }
 }
 
-/// The main voxel world grid
-#[derive(Resource)]
+/// The main voxel world grid, stored as `CHUNK_SIZE`³ chunks
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct VoxelWorld {
-    voxels: Vec<Voxel>,
+    chunks: Vec<Chunk>, // Indexed by `x + z * chunks_x + y * chunks_x * chunks_z` in chunk coordinates
+    chunks_x: usize,
+    chunks_y: usize,
+    chunks_z: usize,
     width: usize,
     height: usize,
     depth: usize,
+    ground_levels: Vec<usize>, // Terrain height of each column, indexed by `x + z * width`
+    pub(super) litter: Vec<VoxelPos>, // Litter voxels still decomposing, in the order they were deposited
+    #[serde(skip)]
+    dirty_columns: Vec<bool>, // Columns whose voxel types changed since lighting last ran
 }
 
 impl VoxelWorld {
+    /// Create a flat world with soil in the lower half
     pub fn new(width: usize, height: usize, depth: usize) -> Self {
-        let total_voxels = width * height * depth;
-        let mut voxels = vec![Voxel::default(); total_voxels];
-
-        // Initialize soil in lower half
-        for y in 0..(height / 2) {
-            for x in 0..width {
-                for z in 0..depth {
-                    let idx = Self::pos_to_index(x, y, z, width, depth);
-                    voxels[idx].voxel_type = VoxelType::Soil;
-                }
-            }
-        }
+        Self::from_heightmap(width, height, depth, &vec![height / 2; width * depth])
+    }
 
-        Self {
-            voxels,
+    /// Create a world with soil filled up to the given height in each column
+    /// `heights` is indexed by `x + z * width`
+    pub fn from_heightmap(width: usize, height: usize, depth: usize, heights: &[usize]) -> Self {
+        let chunks_x = width.div_ceil(CHUNK_SIZE);
+        let chunks_y = height.div_ceil(CHUNK_SIZE);
+        let chunks_z = depth.div_ceil(CHUNK_SIZE);
+        let ground_levels: Vec<usize> = heights.iter().map(|&h| h.min(height)).collect();
+
+        let mut world = Self {
+            chunks: vec![Chunk::new(); chunks_x * chunks_y * chunks_z],
+            chunks_x,
+            chunks_y,
+            chunks_z,
             width,
             height,
             depth,
+            ground_levels,
+            litter: Vec::new(),
+            dirty_columns: Vec::new(),
+        };
+
+        for x in 0..width {
+            for z in 0..depth {
+                let column_height = world.ground_levels[x + z * width];
+                for y in 0..column_height {
+                    let pos = VoxelPos::new(x as i32, y as i32, z as i32);
+                    if let Some(voxel) = world.get_mut(&pos) {
+                        voxel.voxel_type = VoxelType::Soil;
+                    }
+                }
+            }
         }
+
+        world
     }
 
-    fn pos_to_index(x: usize, y: usize, z: usize, width: usize, depth: usize) -> usize {
-        x + z * width + y * width * depth
+    /// Create a world with the dimensions, terrain and soil maps from a config
+    pub fn from_config(config: &SimConfig, terrain_seed: u32) -> Self {
+        let heights = generate_heightmap(config, terrain_seed);
+        let mut world = Self::from_heightmap(
+            config.world_width,
+            config.world_height,
+            config.world_depth,
+            &heights,
+        );
+
+        let (fertility_seed, moisture_seed) = soil_field_seeds(terrain_seed);
+        let fertility = generate_soil_field(
+            config,
+            fertility_seed,
+            config.fertility_frequency,
+            config.fertility_variation,
+        );
+        let moisture = generate_soil_field(
+            config,
+            moisture_seed,
+            config.moisture_frequency,
+            config.moisture_variation,
+        );
+
+        // Soil quality varies by column; every voxel starts full
+        for x in 0..world.width {
+            for z in 0..world.depth {
+                let column = x + z * world.width;
+                for y in 0..world.height {
+                    let pos = VoxelPos::new(x as i32, y as i32, z as i32);
+                    if let Some(voxel) = world.get_mut(&pos) {
+                        let env = &mut voxel.environment;
+                        env.fertility = fertility[column];
+                        env.moisture = moisture[column];
+                        env.nutrients = env.nutrient_capacity(config);
+                        env.water = env.water_capacity(config);
+                    }
+                }
+            }
+        }
+
+        world
     }
 
-    pub fn get(&self, pos: &VoxelPos) -> Option<&Voxel> {
+    /// Index of the chunk and of the voxel within it, for an in-bounds position
+    fn locate(&self, pos: &VoxelPos) -> Option<(usize, usize)> {
         if !self.is_in_bounds(pos) {
             return None;
         }
-        let idx = Self::pos_to_index(
-            pos.x as usize,
-            pos.y as usize,
-            pos.z as usize,
-            self.width,
-            self.depth,
-        );
-        self.voxels.get(idx)
+        let (x, y, z) = (pos.x as usize, pos.y as usize, pos.z as usize);
+        let chunk = self.chunk_index(&ChunkPos::containing(pos))?;
+        let local = Chunk::local_index(x % CHUNK_SIZE, y % CHUNK_SIZE, z % CHUNK_SIZE);
+        Some((chunk, local))
+    }
+
+    fn chunk_index(&self, chunk: &ChunkPos) -> Option<usize> {
+        if chunk.x < 0
+            || chunk.y < 0
+            || chunk.z < 0
+            || chunk.x as usize >= self.chunks_x
+            || chunk.y as usize >= self.chunks_y
+            || chunk.z as usize >= self.chunks_z
+        {
+            return None;
+        }
+        Some(
+            chunk.x as usize
+                + chunk.z as usize * self.chunks_x
+                + chunk.y as usize * self.chunks_x * self.chunks_z,
+        )
+    }
+
+    fn chunk_pos(&self, index: usize) -> ChunkPos {
+        ChunkPos::new(
+            (index % self.chunks_x) as i32,
+            (index / (self.chunks_x * self.chunks_z)) as i32,
+            ((index / self.chunks_x) % self.chunks_z) as i32,
+        )
+    }
+
+    pub fn get(&self, pos: &VoxelPos) -> Option<&Voxel> {
+        let (chunk, local) = self.locate(pos)?;
+        self.chunks[chunk].voxels.get(local)
     }
 
     pub fn get_mut(&mut self, pos: &VoxelPos) -> Option<&mut Voxel> {
-        if !self.is_in_bounds(pos) {
+        let (chunk, local) = self.locate(pos)?;
+        self.chunks[chunk].voxels.get_mut(local)
+    }
+
+    /// Change the type of a voxel, marking its column for relighting and its chunk
+    /// (plus any neighbouring chunk sharing a face with it) for remeshing
+    pub fn set_voxel_type(&mut self, pos: &VoxelPos, voxel_type: VoxelType) {
+        let Some(voxel) = self.get_mut(pos) else {
+            return;
+        };
+        let was_litter = voxel.voxel_type == VoxelType::Litter;
+        voxel.voxel_type = voxel_type;
+
+        // New litter joins the list that decomposition works through
+        if voxel_type == VoxelType::Litter && !was_litter {
+            self.litter.push(*pos);
+        }
+
+        let column = pos.x as usize + pos.z as usize * self.width;
+        if let Some(dirty) = self.dirty_columns.get_mut(column) {
+            *dirty = true;
+        }
+
+        for touched in std::iter::once(*pos).chain(pos.neighbors()) {
+            if let Some(chunk) = self.chunk_index(&ChunkPos::containing(&touched)) {
+                self.chunks[chunk].dirty = true;
+            }
+        }
+    }
+
+    /// Take the (x, z) columns whose voxel types changed since the last call
+    /// `None` means every column must be treated as changed, e.g. for a new or loaded world
+    pub fn take_dirty_columns(&mut self) -> Option<Vec<(i32, i32)>> {
+        let columns = self.width * self.depth;
+        if self.dirty_columns.len() != columns {
+            self.dirty_columns = vec![false; columns];
             return None;
         }
-        let idx = Self::pos_to_index(
-            pos.x as usize,
-            pos.y as usize,
-            pos.z as usize,
-            self.width,
-            self.depth,
-        );
-        self.voxels.get_mut(idx)
+
+        let width = self.width;
+        let dirty = self
+            .dirty_columns
+            .iter()
+            .enumerate()
+            .filter(|(_, dirty)| **dirty)
+            .map(|(column, _)| ((column % width) as i32, (column / width) as i32))
+            .collect();
+        self.dirty_columns.fill(false);
+        Some(dirty)
+    }
+
+    /// Take the chunks that need remeshing since the last call
+    /// Every chunk of a new or loaded world needs meshing
+    pub fn take_dirty_chunks(&mut self) -> Vec<ChunkPos> {
+        let dirty: Vec<ChunkPos> = (0..self.chunks.len())
+            .filter(|&index| self.chunks[index].dirty)
+            .map(|index| self.chunk_pos(index))
+            .collect();
+        for chunk in self.chunks.iter_mut() {
+            chunk.dirty = false;
+        }
+        dirty
+    }
+
+    /// Mark one chunk for remeshing, e.g. when the colours of its voxels change
+    pub fn mark_chunk_dirty(&mut self, chunk: &ChunkPos) {
+        if let Some(index) = self.chunk_index(chunk) {
+            self.chunks[index].dirty = true;
+        }
+    }
+
+    /// Mark every chunk for remeshing, e.g. when the voxel colours change
+    pub fn mark_all_chunks_dirty(&mut self) {
+        for chunk in self.chunks.iter_mut() {
+            chunk.dirty = true;
+        }
     }
 
     pub fn is_in_bounds(&self, pos: &VoxelPos) -> bool {
@@ -213,6 +437,110 @@ impl VoxelWorld {
         self.depth
     }
 
+    /// Whether nothing solid lies directly above a position
+    pub fn is_open_to_sky(&self, pos: &VoxelPos) -> bool {
+        (pos.y + 1..self.height as i32).all(|y| {
+            self.get(&VoxelPos::new(pos.x, y, pos.z))
+                .is_none_or(|voxel| voxel.voxel_type.is_air())
+        })
+    }
+
+    /// Position of the first solid voxel hit by a ray, within `max_distance` world units of `origin`
+    /// `origin` and `direction` are in world coordinates, where each voxel is centred on its position
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<VoxelPos> {
+        let direction = direction.try_normalize()?.to_array();
+        // In voxel units, with voxel boundaries on whole numbers
+        let start = (origin / VOXEL_SIZE + Vec3::splat(0.5)).to_array();
+        let max_t = max_distance / VOXEL_SIZE;
+
+        // Step from voxel to voxel, always crossing the nearest boundary next
+        let mut cell = start.map(|v| v.floor() as i32);
+        let mut t_next = [0.0; 3]; // Distance along the ray to the next boundary on each axis
+        let mut t_delta = [f32::INFINITY; 3]; // Distance along the ray between boundaries on each axis
+        for axis in 0..3 {
+            if direction[axis] > 0.0 {
+                t_next[axis] = (cell[axis] as f32 + 1.0 - start[axis]) / direction[axis];
+                t_delta[axis] = 1.0 / direction[axis];
+            } else if direction[axis] < 0.0 {
+                t_next[axis] = (start[axis] - cell[axis] as f32) / -direction[axis];
+                t_delta[axis] = -1.0 / direction[axis];
+            } else {
+                t_next[axis] = f32::INFINITY;
+            }
+        }
+
+        let mut t = 0.0;
+        while t <= max_t {
+            let pos = VoxelPos::new(cell[0], cell[1], cell[2]);
+            if self.get(&pos).is_some_and(|voxel| voxel.voxel_type.is_solid()) {
+                return Some(pos);
+            }
+
+            let axis = (0..3).min_by(|&a, &b| t_next[a].total_cmp(&t_next[b]))?;
+            cell[axis] += if direction[axis] > 0.0 { 1 } else { -1 };
+            t = t_next[axis];
+            t_next[axis] += t_delta[axis];
+        }
+        None
+    }
+
+    /// Number of ground layers in a column from the original terrain
+    /// Voxels below this height are underground, even where roots have replaced the soil
+    pub fn ground_level(&self, x: i32, z: i32) -> i32 {
+        if x < 0 || z < 0 || x as usize >= self.width || z as usize >= self.depth {
+            return 0;
+        }
+        self.ground_levels[x as usize + z as usize * self.width] as i32
+    }
+
+    /// Y coordinate of the topmost soil voxel in a column, if any
+    pub fn surface_height(&self, x: i32, z: i32) -> Option<i32> {
+        (0..self.height as i32)
+            .rev()
+            .find(|&y| matches!(
+                self.get(&VoxelPos::new(x, y, z)).map(|v| v.voxel_type),
+                Some(VoxelType::Soil)
+            ))
+    }
+
+    /// Iterate mutably over the voxels below the terrain surface, where all soil lies, with their positions
+    /// Chunks entirely above the highest ground are skipped
+    pub fn ground_voxels_mut(&mut self) -> impl Iterator<Item = (VoxelPos, &mut Voxel)> + '_ {
+        let (width, depth) = (self.width, self.depth);
+        let top = self.ground_levels.iter().copied().max().unwrap_or(0);
+        let origins: Vec<VoxelPos> = (0..self.chunks.len())
+            .map(|index| self.chunk_pos(index).origin())
+            .collect();
+        let ground_levels = &self.ground_levels;
+
+        self.chunks
+            .iter_mut()
+            .zip(origins)
+            .filter(move |(_, origin)| (origin.y as usize) < top)
+            .flat_map(move |(chunk, origin)| {
+                chunk.voxels.iter_mut().enumerate().filter_map(move |(index, voxel)| {
+                    let (lx, ly, lz) = Chunk::local_coords(index);
+                    let x = origin.x as usize + lx;
+                    let y = origin.y as usize + ly;
+                    let z = origin.z as usize + lz;
+                    (x < width && z < depth && y < ground_levels[x + z * width])
+                        .then(|| (VoxelPos::new(x as i32, y as i32, z as i32), voxel))
+                })
+            })
+    }
+
+    /// Replace the `plant_id` of every plant voxel using `remap`
+    /// Voxels whose id has no mapping are left unchanged
+    pub fn remap_plant_ids(&mut self, remap: impl Fn(u32) -> Option<u32>) {
+        for voxel in self.chunks.iter_mut().flat_map(|chunk| chunk.voxels.iter_mut()) {
+            if let VoxelType::PlantMaterial { plant_id, .. } = &mut voxel.voxel_type {
+                if let Some(new_id) = remap(*plant_id) {
+                    *plant_id = new_id;
+                }
+            }
+        }
+    }
+
     /// Iterate over all voxel positions
     pub fn iter_positions(&self) -> impl Iterator<Item = VoxelPos> + '_ {
         (0..self.height).flat_map(move |y| {
@@ -2,6 +2,14 @@ use std::process::{Command, Stdio};
 use std::time::Duration;
 use std::thread;
 
+use bevy::prelude::*;
+use plant_evolution_sim::{headless, SimulationPlugin};
+use plant_evolution_sim::config::SimConfig;
+use plant_evolution_sim::plant::PlantBiology;
+use plant_evolution_sim::snapshot::{PendingSnapshot, Snapshot};
+use plant_evolution_sim::world::{VoxelWorld, VoxelType};
+use plant_evolution_sim::statistics::{StatisticsHistory, StatsExporter, CSV_HEADER};
+
 #[test]
 #[ignore] // Ignored by default since it requires a display/graphics environment
 fn test_app_starts_without_crashing() {
@@ -32,3 +40,214 @@ fn test_app_starts_without_crashing() {
 
     println!("Application started successfully and ran for 3 seconds without crashing");
 }
+
+#[test]
+fn test_headless_run_exits_cleanly() {
+    // Headless mode needs no display, so this runs everywhere
+    let output = Command::new(env!("CARGO_BIN_EXE_plant-evolution-sim"))
+        .args(["headless", "--duration", "5"])
+        .output()
+        .expect("Failed to run headless simulation");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        output.status.success(),
+        "Headless run exited with {}: {}",
+        output.status,
+        stderr
+    );
+    assert!(
+        stdout.contains("Headless run finished"),
+        "Headless run did not report completion: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_simulation_plugin_runs_without_rendering() {
+    // The library plugin should work on its own, without rendering, camera or UI plugins
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SimulationPlugin));
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let mut plants = app.world_mut().query::<&PlantBiology>();
+    assert!(
+        plants.iter(app.world()).count() > 0,
+        "No plants were spawned by SimulationPlugin"
+    );
+}
+
+#[test]
+fn test_same_seed_reproduces_statistics() {
+    // Two headless runs with the same seed must produce identical statistics
+    let run = |seed| {
+        let config = SimConfig {
+            seed: Some(seed),
+            ..Default::default()
+        };
+        let mut app = headless::build_headless_app(20.0, config);
+        headless::run_to_completion(&mut app);
+        app.world().resource::<StatisticsHistory>().snapshots.clone()
+    };
+
+    let first = run(42);
+    let second = run(42);
+
+    assert!(!first.is_empty(), "No statistics were collected");
+    assert_eq!(first, second, "Runs with the same seed diverged");
+}
+
+#[test]
+fn test_headless_cli_writes_statistics_csv() {
+    // The CLI overrides should apply and the statistics should land in the output file
+    let out = std::env::temp_dir().join(format!("plant-sim-stats-{}.csv", std::process::id()));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_plant-evolution-sim"))
+        .args(["headless", "--duration", "10", "--seed", "7", "--world-size", "32x32x32", "--initial-seeds", "20"])
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .expect("Failed to run headless simulation");
+
+    assert!(
+        output.status.success(),
+        "Headless run exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let csv = std::fs::read_to_string(&out).expect("Statistics file was not written");
+    let _ = std::fs::remove_file(&out);
+
+    // Run metadata comes first as comment lines, then the header and one row per snapshot
+    assert!(csv.contains("# seed = 7"), "Statistics file is missing the seed: {}", csv);
+    let mut lines = csv.lines().filter(|line| !line.starts_with('#'));
+    assert_eq!(lines.next(), Some(CSV_HEADER));
+    assert!(lines.next().is_some(), "Statistics file has no data rows");
+}
+
+#[test]
+fn test_snapshot_round_trip_restores_plants() {
+    let config = SimConfig {
+        seed: Some(3),
+        ..Default::default()
+    };
+
+    // Run for a while and save the state
+    let mut app = headless::build_headless_app(15.0, config.clone());
+    headless::run_to_completion(&mut app);
+    let original_plants = app.world_mut().query::<&PlantBiology>().iter(app.world()).count();
+
+    let path = std::env::temp_dir().join(format!("plant-sim-{}.snapshot", std::process::id()));
+    Snapshot::capture(app.world_mut()).save(&path).expect("Failed to save snapshot");
+
+    // Restore it into a fresh app
+    let snapshot = Snapshot::load(&path).expect("Failed to load snapshot");
+    let _ = std::fs::remove_file(&path);
+
+    let mut restored = headless::build_headless_app(0.0, config);
+    restored.insert_resource(PendingSnapshot(snapshot));
+    headless::run_to_completion(&mut restored);
+
+    let plant_ids: Vec<u32> = restored
+        .world_mut()
+        .query_filtered::<Entity, With<PlantBiology>>()
+        .iter(restored.world())
+        .map(|entity| entity.index())
+        .collect();
+    assert_eq!(plant_ids.len(), original_plants, "Plant count changed after restoring");
+
+    // Every plant voxel must belong to a restored plant
+    let world = restored.world().resource::<VoxelWorld>();
+    for pos in world.iter_positions() {
+        if let Some(VoxelType::PlantMaterial { plant_id, .. }) = world.get(&pos).map(|v| v.voxel_type) {
+            assert!(plant_ids.contains(&plant_id), "Voxel at {:?} has unknown plant id {}", pos, plant_id);
+        }
+    }
+}
+
+#[test]
+fn test_loaded_headless_run_continues_the_clock() {
+    let config = SimConfig {
+        seed: Some(5),
+        ..Default::default()
+    };
+    let dir = std::env::temp_dir();
+    let saved = dir.join(format!("plant-sim-resume-{}.snapshot", std::process::id()));
+    let resumed = dir.join(format!("plant-sim-resumed-{}.snapshot", std::process::id()));
+    let out = dir.join(format!("plant-sim-resumed-{}.csv", std::process::id()));
+
+    // Save a run partway through
+    let mut app = headless::build_headless_app(15.0, config);
+    headless::run_to_completion(&mut app);
+    let snapshot = Snapshot::capture(app.world_mut());
+    let saved_elapsed = snapshot.elapsed;
+    snapshot.save(&saved).expect("Failed to save snapshot");
+
+    // Continue it from the command line for another 10 seconds
+    let output = Command::new(env!("CARGO_BIN_EXE_plant-evolution-sim"))
+        .args(["headless", "--duration", "10", "--seed", "5"])
+        .arg("--load")
+        .arg(&saved)
+        .arg("--save")
+        .arg(&resumed)
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .expect("Failed to run headless simulation");
+    assert!(
+        output.status.success(),
+        "Headless run exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let final_elapsed = Snapshot::load(&resumed).expect("Failed to load snapshot").elapsed;
+    let csv = std::fs::read_to_string(&out).expect("Statistics file was not written");
+    for path in [&saved, &resumed, &out] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    // The run stops 10 seconds after the saved time, and its statistics carry on from there
+    assert!(
+        (final_elapsed - (saved_elapsed + 10.0)).abs() < 0.5,
+        "Resumed run stopped at {}s, expected {}s",
+        final_elapsed,
+        saved_elapsed + 10.0
+    );
+    let first_row = csv
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .nth(1)
+        .expect("Statistics file has no data rows");
+    let timestamp: f32 = first_row.split(',').next().unwrap().parse().expect("Bad timestamp");
+    assert!(timestamp > saved_elapsed, "Statistics restarted at {}s after loading", timestamp);
+}
+
+#[test]
+fn test_json_lines_export_streams_every_snapshot() {
+    let config = SimConfig {
+        seed: Some(11),
+        ..Default::default()
+    };
+    let path = std::env::temp_dir().join(format!("plant-sim-stats-{}.jsonl", std::process::id()));
+
+    let mut app = headless::build_headless_app(10.0, config.clone());
+    app.insert_resource(StatsExporter::create(&path, 11, &config).expect("Failed to create exporter"));
+    headless::run_to_completion(&mut app);
+    let collected = app.world().resource::<StatisticsHistory>().collected;
+    drop(app);
+
+    let contents = std::fs::read_to_string(&path).expect("Statistics file was not written");
+    let _ = std::fs::remove_file(&path);
+
+    let mut lines = contents.lines();
+    let metadata = lines.next().expect("Statistics file is empty");
+    assert!(metadata.contains("\"metadata\"") && metadata.contains("\"seed\":11"), "Bad metadata line: {}", metadata);
+    assert_eq!(lines.count() as u64, collected, "Not every snapshot was exported");
+}